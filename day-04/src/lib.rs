@@ -1,25 +1,22 @@
-use std::ops::Range;
+pub mod utils;
+use utils::interval::IntervalSet;
 
 pub fn part_one(input: &str) -> usize {
     parse(input, |first, second| {
-        (first.start >= second.start && first.end <= second.end)
-            || (second.start >= first.start && second.end <= first.end)
+        first.is_superset(second) || second.is_superset(first)
     })
 }
 
 pub fn part_two(input: &str) -> usize {
-    parse(input, |first, second| {
-        (first.start >= second.start && first.start <= second.end)
-            || (first.end >= second.start && first.end <= second.end)
-            || (second.start >= first.start && second.start <= first.end)
-            || (second.end >= first.start && second.end <= first.end)
-    })
+    parse(input, |first, second| first.intersects(second))
 }
 
-fn parse(input: &str, filter: fn(&Range<u32>, &Range<u32>) -> bool) -> usize {
-    fn str_to_range(range_string: &str) -> Range<u32> {
+fn parse(input: &str, filter: fn(&IntervalSet, &IntervalSet) -> bool) -> usize {
+    fn str_to_range(range_string: &str) -> IntervalSet {
         let (from, to) = range_string.split_once('-').unwrap();
-        from.parse().unwrap()..to.parse().unwrap()
+        [(from.parse().unwrap(), to.parse().unwrap())]
+            .into_iter()
+            .collect()
     }
 
     input
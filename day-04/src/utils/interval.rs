@@ -0,0 +1,242 @@
+// set of integers stored as sorted, disjoint, inclusive (start, end) intervals.
+// touching intervals are always merged, so [1-3] + [4-6] is stored as [1-6] and
+// two sets holding the same integers are always equal.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct IntervalSet {
+    intervals: Vec<(i64, i64)>,
+}
+
+impl IntervalSet {
+    pub fn new() -> IntervalSet {
+        IntervalSet {
+            intervals: Vec::new(),
+        }
+    }
+
+    pub fn insert(&mut self, start: i64, end: i64) {
+        if start > end {
+            return;
+        }
+
+        // every interval overlapping or directly adjacent to the new one gets
+        // folded into it, which is always a contiguous run in the sorted list
+        let first = self
+            .intervals
+            .partition_point(|&(_, e)| e < start.saturating_sub(1));
+        let last = self
+            .intervals
+            .partition_point(|&(s, _)| s <= end.saturating_add(1));
+
+        let mut merged = (start, end);
+        if first < last {
+            merged.0 = merged.0.min(self.intervals[first].0);
+            merged.1 = merged.1.max(self.intervals[last - 1].1);
+        }
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    pub fn remove(&mut self, start: i64, end: i64) {
+        if start > end {
+            return;
+        }
+
+        let first = self.intervals.partition_point(|&(_, e)| e < start);
+        let last = self.intervals.partition_point(|&(s, _)| s <= end);
+        if first >= last {
+            return;
+        }
+
+        // only the first and last overlapping intervals can poke out either
+        // side, and there's nothing either side of i64::MIN or i64::MAX
+        let (first_start, last_end) = (self.intervals[first].0, self.intervals[last - 1].1);
+        let remaining = [
+            start
+                .checked_sub(1)
+                .filter(|before| first_start <= *before)
+                .map(|before| (first_start, before)),
+            end.checked_add(1)
+                .filter(|after| *after <= last_end)
+                .map(|after| (after, last_end)),
+        ];
+        self.intervals
+            .splice(first..last, remaining.into_iter().flatten());
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        let index = self.intervals.partition_point(|&(_, e)| e < value);
+        index < self.intervals.len() && self.intervals[index].0 <= value
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    // total number of integers covered by the set, which can be one more
+    // than fits in a u64 if the whole range of i64 is covered.
+    pub fn len(&self) -> u128 {
+        self.intervals
+            .iter()
+            .map(|(s, e)| e.abs_diff(*s) as u128 + 1)
+            .sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.intervals.iter().copied()
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut out = self.clone();
+        other.iter().for_each(|(s, e)| out.insert(s, e));
+        out
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut out = IntervalSet::new();
+        let (mut left, mut right) = (0, 0);
+
+        while left < self.intervals.len() && right < other.intervals.len() {
+            let (ls, le) = self.intervals[left];
+            let (rs, re) = other.intervals[right];
+            if ls.max(rs) <= le.min(re) {
+                out.intervals.push((ls.max(rs), le.min(re)));
+            }
+            if le < re {
+                left += 1;
+            } else {
+                right += 1;
+            }
+        }
+
+        out
+    }
+
+    // everything between min and max (inclusive) which is not in this set
+    pub fn complement(&self, min: i64, max: i64) -> IntervalSet {
+        let mut out = IntervalSet::new();
+        out.insert(min, max);
+        self.iter().for_each(|(s, e)| out.remove(s, e));
+        out
+    }
+
+    // the holes between consecutive intervals, ignoring anything unbounded
+    // before the first or after the last interval
+    pub fn gaps(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.intervals
+            .windows(2)
+            .map(|pair| (pair[0].1 + 1, pair[1].0 - 1))
+    }
+
+    pub fn is_superset(&self, other: &IntervalSet) -> bool {
+        self.intersection(other) == *other
+    }
+
+    pub fn intersects(&self, other: &IntervalSet) -> bool {
+        !self.intersection(other).is_empty()
+    }
+}
+
+impl FromIterator<(i64, i64)> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = (i64, i64)>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        iter.into_iter().for_each(|(s, e)| set.insert(s, e));
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn intervals(set: &IntervalSet) -> Vec<(i64, i64)> {
+        set.iter().collect()
+    }
+
+    #[test]
+    fn test_insert_merges() {
+        let mut set = IntervalSet::new();
+        set.insert(10, 12);
+        set.insert(1, 3);
+        set.insert(5, 6);
+        assert_eq!(vec![(1, 3), (5, 6), (10, 12)], intervals(&set));
+
+        set.insert(4, 4); // adjacent on both sides
+        assert_eq!(vec![(1, 6), (10, 12)], intervals(&set));
+
+        set.insert(0, 11);
+        assert_eq!(vec![(0, 12)], intervals(&set));
+        assert_eq!(13, set.len());
+    }
+
+    #[test]
+    fn test_insert_keeps_gaps() {
+        let set: IntervalSet = [(1, 3), (6, 8)].into_iter().collect();
+        assert_eq!(vec![(4, 5)], set.gaps().collect::<Vec<_>>());
+        assert!(!set.contains(4));
+        assert!(set.contains(3));
+        assert!(set.contains(6));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut set: IntervalSet = [(0, 10), (20, 30)].into_iter().collect();
+        set.remove(5, 22);
+        assert_eq!(vec![(0, 4), (23, 30)], intervals(&set));
+
+        set.remove(0, 4);
+        assert_eq!(vec![(23, 30)], intervals(&set));
+
+        set.remove(25, 25);
+        assert_eq!(vec![(23, 24), (26, 30)], intervals(&set));
+
+        set.remove(100, 200);
+        assert_eq!(7, set.len());
+    }
+
+    #[test]
+    fn test_remove_at_the_limits() {
+        let mut set: IntervalSet = [(i64::MIN, -10), (10, i64::MAX)].into_iter().collect();
+        set.remove(20, i64::MAX);
+        assert_eq!(vec![(i64::MIN, -10), (10, 19)], intervals(&set));
+
+        set.remove(i64::MIN, -11);
+        assert_eq!(vec![(-10, -10), (10, 19)], intervals(&set));
+        assert_eq!(11, set.len());
+
+        let mut full: IntervalSet = [(i64::MIN, i64::MAX)].into_iter().collect();
+        assert_eq!(1 << 64, full.len());
+        full.remove(0, 0);
+        assert_eq!(vec![(i64::MIN, -1), (1, i64::MAX)], intervals(&full));
+        assert_eq!(u64::MAX as u128, full.len());
+
+        set.remove(i64::MIN, i64::MAX);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let left: IntervalSet = [(0, 5), (10, 15)].into_iter().collect();
+        let right: IntervalSet = [(3, 12)].into_iter().collect();
+
+        assert_eq!(vec![(0, 15)], intervals(&left.union(&right)));
+        assert_eq!(
+            vec![(3, 5), (10, 12)],
+            intervals(&left.intersection(&right))
+        );
+        assert_eq!(
+            vec![(-2, -1), (6, 9), (16, 20)],
+            intervals(&left.complement(-2, 20))
+        );
+        assert!(left.intersects(&right));
+        assert!(!left.is_superset(&right));
+        assert!(left.union(&right).is_superset(&right));
+    }
+
+    #[test]
+    fn test_empty() {
+        let set = IntervalSet::new();
+        assert!(set.is_empty());
+        assert_eq!(0, set.len());
+        assert_eq!(vec![(1, 5)], intervals(&set.complement(1, 5)));
+        assert_eq!(0, set.gaps().count());
+    }
+}
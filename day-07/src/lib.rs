@@ -1,206 +1,666 @@
-use itertools::Itertools;
-use std::collections::HashMap;
-use std::collections::HashSet;
-
-// Represents a directory on the file system, used to access the various
-// listings and ancestors from the file system. Can be cd'd into.
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
-struct Directory {
+pub mod utils;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use utils::search::{dfs, SearchState};
+
+// What a node in the file system holds. Directories keep their children by name
+// so that listings come out sorted, and remember whether they've been listed so
+// that a later listing can be checked against the first.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Contents {
+    File,
+    Directory {
+        children: BTreeMap<String, usize>,
+        listed: bool,
+    },
+}
+
+// A file or directory in the file system, referred to by its index. The size of
+// a directory is the total of everything inside it, kept up to date as entries
+// are added so it never needs recalculating.
+#[derive(Clone, Debug)]
+struct Node {
     name: String,
-    path: String,
+    parent: Option<usize>,
+    size: u32,
+    contents: Contents,
 }
 
-impl Directory {
-    fn new(name: &str, parent: Option<Directory>) -> Directory {
-        Directory {
-            name: name.to_string(),
-            path: match parent {
-                Some(dir) => dir.full_path(),
-                None => "".to_string(),
-            },
-        }
+impl Node {
+    fn is_dir(&self) -> bool {
+        matches!(self.contents, Contents::Directory { .. })
     }
+}
 
-    fn full_path(&self) -> String {
-        self.path.clone() + &self.name
-    }
+// What the queries return about an entry in the file system.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Entry {
+    pub path: String,
+    pub size: u32,
+    pub is_dir: bool,
+}
+
+const ROOT: usize = 0;
+
+// The file system as a whole, every node lives in the one arena with the root
+// directory first.
+#[derive(Clone, Debug)]
+pub struct FileSystem {
+    nodes: Vec<Node>,
 }
 
-// Represents the contents of a directory. Separate from Directory to retain
-// easy memory access of a directory via cloning and whatnot.
-struct DirectoryListing {
-    directories: HashSet<Directory>,
-    files: HashSet<File>,
+impl Default for FileSystem {
+    fn default() -> Self {
+        FileSystem::new()
+    }
 }
 
-impl DirectoryListing {
-    fn new() -> DirectoryListing {
-        DirectoryListing {
-            directories: HashSet::new(),
-            files: HashSet::new(),
+impl FileSystem {
+    pub fn new() -> FileSystem {
+        FileSystem {
+            nodes: vec![Node {
+                name: "".to_string(),
+                parent: None,
+                size: 0,
+                contents: Contents::Directory {
+                    children: BTreeMap::new(),
+                    listed: false,
+                },
+            }],
         }
     }
 
-    fn mkdir(&mut self, dir: Directory) {
-        self.directories.insert(dir);
+    fn children(&self, dir: usize) -> Option<&BTreeMap<String, usize>> {
+        match &self.nodes[dir].contents {
+            Contents::Directory { children, .. } => Some(children),
+            Contents::File => None,
+        }
     }
 
-    fn touch(&mut self, file: File) {
-        self.files.insert(file);
+    // Finds the node at the path. Absolute paths start from the root, anything
+    // else from the directory `from`. Handles `.` and `..` along the way.
+    fn resolve(&self, from: usize, path: &str) -> Result<usize, String> {
+        let mut current = match path.starts_with('/') {
+            true => ROOT,
+            false => from,
+        };
+
+        for component in path.split('/') {
+            current = match component {
+                "" | "." => current,
+                ".." => match self.nodes[current].parent {
+                    Some(parent) => parent,
+                    None => return Err("can't go above /".to_string()),
+                },
+                name => match self.children(current) {
+                    Some(children) => match children.get(name) {
+                        Some(child) => *child,
+                        None => return Err(format!("{} not found", self.join(current, name))),
+                    },
+                    None => return Err(format!("{} is not a directory", self.path(current))),
+                },
+            };
+        }
+
+        Ok(current)
     }
-}
 
-// A file exists in a directory, it has a size. The size of a directory is equal
-// to the sum of all the files and the directories contained within.
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
-struct File {
-    name: String,
-    size: u32,
-}
+    fn path(&self, node: usize) -> String {
+        match self.nodes[node].parent {
+            Some(parent) => self.join(parent, &self.nodes[node].name),
+            None => "/".to_string(),
+        }
+    }
 
-impl File {
-    fn new(name: String, size: u32) -> File {
-        File { name, size }
+    fn join(&self, dir: usize, name: &str) -> String {
+        match dir {
+            ROOT => format!("/{}", name),
+            _ => format!("{}/{}", self.path(dir), name),
+        }
     }
-}
 
-// The file system as a whole. Retains a memory of ownership as well as the
-// index of where things are stored on disk.
-struct FileSystem {
-    root: Directory,
-    ancestors: HashMap<Directory, Directory>,
-    listings: HashMap<Directory, DirectoryListing>,
-}
+    // Adds a file (with a size) or directory (without) to the directory, adding
+    // the file's size to every directory above it.
+    fn add(&mut self, dir: usize, name: &str, size: Option<u32>) -> usize {
+        let node = self.nodes.len();
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: Some(dir),
+            size: size.unwrap_or(0),
+            contents: match size {
+                Some(_) => Contents::File,
+                None => Contents::Directory {
+                    children: BTreeMap::new(),
+                    listed: false,
+                },
+            },
+        });
 
-impl FileSystem {
-    fn new() -> FileSystem {
-        let mut fs = FileSystem {
-            root: Directory::new("/", None),
-            listings: HashMap::new(),
-            ancestors: HashMap::new(),
-        };
-        fs.listings.insert(fs.root.clone(), DirectoryListing::new());
-        fs
+        if let Contents::Directory { children, .. } = &mut self.nodes[dir].contents {
+            children.insert(name.to_string(), node);
+        }
+
+        let mut ancestor = Some(dir);
+        while let Some(current) = ancestor {
+            self.nodes[current].size += size.unwrap_or(0);
+            ancestor = self.nodes[current].parent;
+        }
+
+        node
     }
 
-    fn mkdir(&mut self, parent: Directory, name: &str) -> Directory {
-        let dir = Directory::new(name, Some(parent.clone()));
-        let listings = self.listings.get_mut(&parent).unwrap();
-        listings.mkdir(dir.clone());
+    // Changes the size of a file, along with every directory above it.
+    fn resize(&mut self, file: usize, size: u32) {
+        let previous = self.nodes[file].size;
+        let mut ancestor = Some(file);
+        while let Some(current) = ancestor {
+            self.nodes[current].size = self.nodes[current].size - previous + size;
+            ancestor = self.nodes[current].parent;
+        }
+    }
 
-        if !self.listings.contains_key(&dir) {
-            self.ancestors.insert(dir.clone(), parent);
-            self.listings.insert(dir.clone(), DirectoryListing::new());
+    // The size the node would have been listed with, directories don't have one.
+    fn listed_size(&self, node: usize) -> Option<u32> {
+        match self.nodes[node].contents {
+            Contents::File => Some(self.nodes[node].size),
+            Contents::Directory { .. } => None,
         }
-        dir
     }
 
-    fn touch(&mut self, dir: Directory, size: u32, name: &str) {
-        let file = File::new(name.to_string(), size);
-        let listings = self.listings.get_mut(&dir).unwrap();
-        listings.touch(file);
+    fn entry(&self, node: usize) -> Entry {
+        Entry {
+            path: self.path(node),
+            size: self.nodes[node].size,
+            is_dir: self.nodes[node].is_dir(),
+        }
     }
 
-    fn dir_size(&self, dir: &Directory) -> u32 {
-        let listings = self.listings.get(dir).unwrap();
-        let file_sizes: u32 = listings.files.iter().map(|file| file.size).sum();
+    // Total size of the file or directory at the path.
+    pub fn du(&self, path: &str) -> Result<u32, String> {
+        Ok(self.nodes[self.resolve(ROOT, path)?].size)
+    }
 
-        let dir_sizes: u32 = listings
-            .directories
-            .iter()
-            .map(|subdir| self.dir_size(subdir))
-            .sum();
+    // Space used by everything on disk.
+    pub fn used(&self) -> u32 {
+        self.nodes[ROOT].size
+    }
 
-        file_sizes + dir_sizes
+    // Everything at or below the path for which the predicate holds, in the
+    // same order as they'd appear in the tree.
+    pub fn find<F>(&self, path: &str, predicate: F) -> Result<Vec<Entry>, String>
+    where
+        F: Fn(&Entry) -> bool,
+    {
+        let mut found = vec![];
+        let mut stack = vec![self.resolve(ROOT, path)?];
+
+        while let Some(node) = stack.pop() {
+            let entry = self.entry(node);
+            if predicate(&entry) {
+                found.push(entry);
+            }
+            if let Some(children) = self.children(node) {
+                stack.extend(children.values().rev());
+            }
+        }
+
+        Ok(found)
+    }
+
+    // Every directory along with its total size.
+    pub fn directories(&self) -> Vec<Entry> {
+        self.find("/", |entry| entry.is_dir).unwrap()
+    }
+
+    // Draws everything at or below the path in the same style as the puzzle.
+    pub fn tree(&self, path: &str) -> Result<String, String> {
+        let mut lines = vec![];
+        let mut stack = vec![(self.resolve(ROOT, path)?, 0)];
+
+        while let Some((node, depth)) = stack.pop() {
+            let name = match node {
+                ROOT => "/",
+                _ => &self.nodes[node].name,
+            };
+            let description = match self.nodes[node].contents {
+                Contents::File => format!("file, size={}", self.nodes[node].size),
+                Contents::Directory { .. } => "dir".to_string(),
+            };
+            lines.push(format!(
+                "{}- {} ({})",
+                "  ".repeat(depth),
+                name,
+                description
+            ));
+
+            if let Some(children) = self.children(node) {
+                stack.extend(children.values().rev().map(|child| (*child, depth + 1)));
+            }
+        }
+
+        Ok(lines.join("\n"))
     }
+}
+
+// A single line of `ls` output, directories don't have a size.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Listed {
+    line: usize,
+    name: String,
+    size: Option<u32>,
+}
+
+fn describe(size: Option<u32>) -> String {
+    match size {
+        Some(size) => format!("size {}", size),
+        None => "a directory".to_string(),
+    }
+}
+
+// What to do when a directory is listed again with different contents. Either
+// keep what we had and ignore the new listing, or combine the two with the
+// newer file sizes winning. Entries can't change between file and directory.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Conflicts {
+    Reject,
+    Merge,
+}
+
+// Something wrong with the transcript, and the line we spotted it on.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Problem {
+    pub line: usize,
+    pub message: String,
+}
 
-    fn parent(&self, dir: &Directory) -> Directory {
-        assert_ne!(self.root, *dir);
-        let parent = self.ancestors.get(dir).unwrap();
-        parent.clone()
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
     }
 }
 
+// The file system we ended up with after replaying a transcript, along with
+// every problem found in it, in line order.
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub file_system: FileSystem,
+    pub problems: Vec<Problem>,
+}
+
 // The shell represents our interaction state with the file system. Contains the
-// fire system as well as what directory we're currently in.
+// file system as well as what directory we're currently in, plus the problems
+// we've run into so far.
 struct Shell {
     file_system: FileSystem,
-    current: Directory,
+    current: usize,
+    conflicts: Conflicts,
+    problems: Vec<Problem>,
 }
 
 impl Shell {
-    fn new(file_system: FileSystem) -> Shell {
-        let current = file_system.root.clone();
+    fn new(file_system: FileSystem, conflicts: Conflicts) -> Shell {
         Shell {
             file_system,
-            current,
+            current: ROOT,
+            conflicts,
+            problems: vec![],
         }
     }
 
-    fn cd(&mut self, path: &str) {
-        match path {
-            "/" => self.current = self.file_system.root.clone(),
-            ".." => self.current = self.file_system.parent(&self.current),
-            _ => self.current = self.file_system.mkdir(self.current.clone(), path),
-        };
+    fn problem(&mut self, line: usize, message: String) {
+        self.problems.push(Problem { line, message });
     }
 
-    fn touch(&mut self, size: u32, name: &str) {
-        self.file_system.touch(self.current.clone(), size, name);
+    // Changes directory, staying where we are if the path doesn't lead to one.
+    fn cd(&mut self, line: usize, path: &str) {
+        match self.file_system.resolve(self.current, path) {
+            Ok(dir) if self.file_system.nodes[dir].is_dir() => self.current = dir,
+            Ok(file) => {
+                let path = self.file_system.path(file);
+                self.problem(line, format!("{} is not a directory", path));
+            }
+            Err(err) => self.problem(line, err),
+        }
     }
 
-    fn dir_sizes(&self) -> Vec<(Directory, u32)> {
-        self.file_system
-            .listings
-            .iter()
-            .map(|(key, _)| (key.clone(), self.file_system.dir_size(key)))
-            .sorted_by(|left, right| Ord::cmp(&right.1, &left.1))
-            .collect()
+    // Records the listing of the current directory. The first listing creates
+    // everything in it, every listing after that is checked against what we
+    // already know and conflicts are handled depending on the mode.
+    fn ls(&mut self, ls_line: usize, listing: &[Listed]) {
+        let dir = self.current;
+        let Contents::Directory { listed, .. } = self.file_system.nodes[dir].contents else {
+            unreachable!("we can only be in a directory");
+        };
+        let merge = self.conflicts == Conflicts::Merge;
+        let mut seen = vec![];
+
+        for entry in listing {
+            let path = self.file_system.join(dir, &entry.name);
+            if seen.contains(&entry.name.as_str()) {
+                self.problem(entry.line, format!("{} is listed twice", path));
+                continue;
+            }
+
+            let existing = self
+                .file_system
+                .children(dir)
+                .and_then(|children| children.get(&entry.name))
+                .copied();
+
+            match existing {
+                None => {
+                    if listed {
+                        self.problem(entry.line, format!("{} wasn't listed before", path));
+                    }
+                    if !listed || merge {
+                        self.file_system.add(dir, &entry.name, entry.size);
+                    }
+                }
+                Some(child) => {
+                    let previous = self.file_system.listed_size(child);
+                    if previous != entry.size {
+                        self.problem(
+                            entry.line,
+                            format!(
+                                "{} is listed as {} but was {}",
+                                path,
+                                describe(entry.size),
+                                describe(previous)
+                            ),
+                        );
+                        if let (true, Some(_), Some(size)) = (merge, previous, entry.size) {
+                            self.file_system.resize(child, size);
+                        }
+                    }
+                }
+            }
+            seen.push(entry.name.as_str());
+        }
+
+        if listed {
+            let missing: Vec<String> = self
+                .file_system
+                .children(dir)
+                .unwrap()
+                .keys()
+                .filter(|name| !seen.contains(&name.as_str()))
+                .map(|name| self.file_system.join(dir, name))
+                .collect();
+            for path in missing {
+                self.problem(ls_line, format!("{} is missing from the listing", path));
+            }
+        }
+
+        if let Contents::Directory { listed, .. } = &mut self.file_system.nodes[dir].contents {
+            *listed = true;
+        }
     }
 }
 
-fn run_commands(input: &str) -> Shell {
-    let mut shell = Shell::new(FileSystem::new());
+// Replays the transcript, building up the file system as we go and noting
+// every problem with it rather than stopping at the first.
+pub fn check(input: &str, conflicts: Conflicts) -> Report {
+    let mut shell = Shell::new(FileSystem::new(), conflicts);
+    // The line the current `ls` was on and its output so far.
+    let mut listing: Option<(usize, Vec<Listed>)> = None;
+
+    for (index, line) in input.lines().enumerate() {
+        let line_number = index + 1;
 
-    input
-        .lines()
-        .map(|line| line.split(' ').collect())
-        .for_each(|parts: Vec<&str>| match parts[0] {
-            "$" => {
-                if parts[1].eq("cd") {
-                    shell.cd(parts[2])
+        match line.split(' ').collect::<Vec<_>>().as_slice() {
+            ["$", command @ ..] => {
+                if let Some((ls_line, entries)) = listing.take() {
+                    shell.ls(ls_line, &entries);
                 }
+                match command {
+                    ["cd", path] => shell.cd(line_number, path),
+                    ["ls"] => listing = Some((line_number, vec![])),
+                    _ => shell.problem(line_number, format!("unknown command {}", line)),
+                }
+            }
+            [size, name] => {
+                let Some((_, entries)) = &mut listing else {
+                    shell.problem(line_number, "output without an ls".to_string());
+                    continue;
+                };
+                let size = match *size {
+                    "dir" => None,
+                    size => match size.parse() {
+                        Ok(size) => Some(size),
+                        Err(_) => {
+                            shell.problem(line_number, format!("couldn't parse {}", line));
+                            continue;
+                        }
+                    },
+                };
+                entries.push(Listed {
+                    line: line_number,
+                    name: name.to_string(),
+                    size,
+                });
             }
-            "dir" => (),
-            _ => shell.touch(parts[0].parse().unwrap(), parts[1]),
+            _ => shell.problem(line_number, format!("couldn't parse {}", line)),
+        }
+    }
+
+    if let Some((ls_line, entries)) = listing {
+        shell.ls(ls_line, &entries);
+    }
+
+    let mut problems = shell.problems;
+    problems.sort_by_key(|problem| problem.line);
+    Report {
+        file_system: shell.file_system,
+        problems,
+    }
+}
+
+// Replays the transcript, failing on the first problem with it.
+pub fn run_commands(input: &str) -> Result<FileSystem, String> {
+    let report = check(input, Conflicts::Reject);
+    match report.problems.first() {
+        Some(problem) => Err(problem.to_string()),
+        None => Ok(report.file_system),
+    }
+}
+
+// What it'd take to free up enough space, `needed` is how much more space we
+// need free. Both the smallest directory that frees enough on its own, and the
+// set of directories (none inside another) which frees enough while deleting
+// as little as possible. Both are empty if there's already enough space.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cleanup {
+    pub needed: u32,
+    pub directory: Option<Entry>,
+    pub directories: Vec<Entry>,
+}
+
+// A directory we could delete, in tree order. Everything inside it comes
+// straight after it, up until `end`.
+#[derive(Clone, Debug)]
+struct Candidate {
+    node: usize,
+    size: u32,
+    end: usize,
+    // The most we could free from here onwards, and the least we'd free if we
+    // deleted anything else.
+    reachable: u32,
+    smallest: u32,
+}
+
+// A partial choice of directories to delete. Candidates are considered in
+// order, and taking one skips everything inside it so we never pick a
+// directory that's already being deleted along with its parent.
+#[derive(Clone, Debug)]
+struct Selection<'a> {
+    candidates: &'a [Candidate],
+    needed: u32,
+    next: usize,
+    total: u32,
+    chosen: Vec<usize>,
+}
+
+impl SearchState for Selection<'_> {
+    // None until we've freed enough, then the smaller the total the better.
+    type Score = Option<Reverse<u32>>;
+
+    fn successors(&self) -> Vec<Self> {
+        if self.total >= self.needed || self.next == self.candidates.len() {
+            return vec![];
+        }
+
+        let candidate = &self.candidates[self.next];
+        let skip = Selection {
+            next: self.next + 1,
+            ..self.clone()
+        };
+        let mut take = Selection {
+            next: candidate.end,
+            total: self.total + candidate.size,
+            ..self.clone()
+        };
+        take.chosen.push(self.next);
+
+        vec![skip, take]
+    }
+
+    fn score(&self) -> Option<Reverse<u32>> {
+        (self.total >= self.needed).then_some(Reverse(self.total))
+    }
+
+    // Deleting more only adds to the total, so we can't do better than
+    // deleting the smallest candidate left - or exactly what's needed. Nothing
+    // at all if what's left can't free enough.
+    fn bound(&self) -> Option<Option<Reverse<u32>>> {
+        if self.total >= self.needed {
+            return Some(self.score());
+        }
+
+        match self.candidates.get(self.next) {
+            Some(next) if self.total + next.reachable >= self.needed => {
+                Some(Some(Reverse(self.needed.max(self.total + next.smallest))))
+            }
+            _ => Some(None),
+        }
+    }
+}
+
+impl FileSystem {
+    // Every directory in tree order, ready to be searched for a cleanup.
+    fn candidates(&self) -> Vec<Candidate> {
+        let mut order = vec![];
+        let mut stack = vec![ROOT];
+        while let Some(node) = stack.pop() {
+            order.push(node);
+            if let Some(children) = self.children(node) {
+                stack.extend(children.values().rev().filter(|c| self.nodes[**c].is_dir()));
+            }
+        }
+
+        // A directory's candidates run until the end of its last descendant.
+        let positions: HashMap<usize, usize> = order
+            .iter()
+            .enumerate()
+            .map(|(i, node)| (*node, i))
+            .collect();
+        let mut descendants = vec![0; order.len()];
+        for node in &order {
+            let mut ancestor = self.nodes[*node].parent;
+            while let Some(current) = ancestor {
+                descendants[positions[&current]] += 1;
+                ancestor = self.nodes[current].parent;
+            }
+        }
+
+        let mut candidates: Vec<Candidate> = order
+            .iter()
+            .enumerate()
+            .map(|(i, node)| Candidate {
+                node: *node,
+                size: self.nodes[*node].size,
+                end: i + 1 + descendants[i],
+                reachable: 0,
+                smallest: 0,
+            })
+            .collect();
+        for i in (0..candidates.len()).rev() {
+            let reachable = candidates
+                .get(candidates[i].end)
+                .map_or(0, |after| after.reachable);
+            let smallest = candidates
+                .get(i + 1)
+                .map_or(u32::MAX, |after| after.smallest);
+            candidates[i].reachable = candidates[i].size + reachable;
+            candidates[i].smallest = candidates[i].size.min(smallest);
+        }
+
+        candidates
+    }
+
+    // Plans how to get at least `target` free on a disk holding `capacity`.
+    pub fn plan_cleanup(&self, capacity: u32, target: u32) -> Result<Cleanup, String> {
+        let used = self.used();
+        if used > capacity {
+            return Err(format!(
+                "{} is in use but the disk only holds {}",
+                used, capacity
+            ));
+        }
+
+        let needed = target.saturating_sub(capacity - used);
+        if needed > used {
+            return Err(format!("can't free {}, only {} is in use", needed, used));
+        }
+        if needed == 0 {
+            return Ok(Cleanup {
+                needed,
+                directory: None,
+                directories: vec![],
+            });
+        }
+
+        let directory = self
+            .directories()
+            .into_iter()
+            .filter(|dir| dir.size >= needed)
+            .min_by_key(|dir| dir.size);
+
+        let candidates = self.candidates();
+        let best = dfs(Selection {
+            candidates: &candidates,
+            needed,
+            next: 0,
+            total: 0,
+            chosen: vec![],
         });
-    shell
+        let directories = best
+            .chosen
+            .iter()
+            .map(|i| self.entry(candidates[*i].node))
+            .collect();
+
+        Ok(Cleanup {
+            needed,
+            directory,
+            directories,
+        })
+    }
 }
 
 pub fn part_one(input: &str) -> u32 {
     run_commands(input)
-        .dir_sizes()
+        .unwrap()
+        .directories()
         .iter()
-        .filter(|(_, size)| size <= &100_000)
-        .map(|(_, size)| size)
+        .filter(|dir| dir.size <= 100_000)
+        .map(|dir| dir.size)
         .sum()
 }
 
 pub fn part_two(input: &str) -> u32 {
-    let shell = run_commands(input);
-    let dir_sizes = shell.dir_sizes();
-    let total_disk_size = 70_000_000;
-    let desired_unused = 30_000_000;
-    let current_unused = total_disk_size - shell.dir_sizes()[0].1;
-    let deletion_size = desired_unused - current_unused;
-
-    let to_delete: Vec<u32> = dir_sizes
-        .iter()
-        .filter(|(_, size)| size >= &deletion_size)
-        .map(|(_, size)| *size)
-        .collect();
-
-    *to_delete.last().unwrap()
+    let file_system = run_commands(input).unwrap();
+    let cleanup = file_system.plan_cleanup(70_000_000, 30_000_000).unwrap();
+    cleanup.directory.unwrap().size
 }
 
 #[cfg(test)]
@@ -257,4 +717,222 @@ mod tests {
     fn test_part_two() {
         assert_eq!(2832508, part_two(&read_input_file()));
     }
+
+    #[test]
+    fn test_paths() {
+        let fs = run_commands(EXAMPLE).unwrap();
+        assert_eq!(Ok(584), fs.du("/a/e/i"));
+        assert_eq!(Ok(94853), fs.du("/a"));
+        assert_eq!(Ok(24933642), fs.du("/d/../a/./../d"));
+        assert_eq!(Ok(48381165), fs.du("/"));
+        assert_eq!(Err("/a/x not found".to_string()), fs.du("/a/x"));
+        assert_eq!(
+            Err("/b.txt is not a directory".to_string()),
+            fs.du("/b.txt/x")
+        );
+        assert_eq!(Err("can't go above /".to_string()), fs.du("/.."));
+    }
+
+    #[test]
+    fn test_tree() {
+        let fs = run_commands(EXAMPLE).unwrap();
+        let expected = indoc! {"
+            - / (dir)
+              - a (dir)
+                - e (dir)
+                  - i (file, size=584)
+                - f (file, size=29116)
+                - g (file, size=2557)
+                - h.lst (file, size=62596)
+              - b.txt (file, size=14848514)
+              - c.dat (file, size=8504156)
+              - d (dir)
+                - d.ext (file, size=5626152)
+                - d.log (file, size=8033020)
+                - j (file, size=4060174)
+                - k (file, size=7214296)"};
+        assert_eq!(Ok(expected.to_string()), fs.tree("/"));
+        assert_eq!(
+            Ok("- e (dir)\n  - i (file, size=584)".to_string()),
+            fs.tree("a/e")
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let fs = run_commands(EXAMPLE).unwrap();
+        let paths = |entries: Vec<Entry>| -> Vec<String> {
+            entries.into_iter().map(|entry| entry.path).collect()
+        };
+        assert_eq!(
+            vec!["/", "/a", "/a/e", "/d"],
+            paths(fs.find("/", |entry| entry.is_dir).unwrap())
+        );
+        assert_eq!(
+            vec!["/d/d.ext", "/d/d.log"],
+            paths(fs.find("/d", |entry| entry.path.contains(".")).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_transcript_errors() {
+        let relisted = format!("{}\n$ cd /\n$ ls\ndir a\n1 b.txt", EXAMPLE);
+        assert_eq!(
+            Some("line 25: /c.dat is missing from the listing".to_string()),
+            run_commands(&relisted).err()
+        );
+
+        let same_again = format!("{}\n$ cd /a/e\n$ ls\n584 i", EXAMPLE);
+        assert!(run_commands(&same_again).is_ok());
+
+        // A duplicate is reported even when both lines agree
+        let twice = "$ cd /\n$ ls\n100 a.txt\n100 a.txt";
+        assert_eq!(
+            Some("line 4: /a.txt is listed twice".to_string()),
+            run_commands(twice).err()
+        );
+        let report = check(twice, Conflicts::Merge);
+        assert_eq!(1, report.problems.len());
+        assert_eq!(100, report.file_system.used());
+
+        assert_eq!(
+            Some("line 2: /x not found".to_string()),
+            run_commands("$ cd /\n$ cd x").err()
+        );
+        assert_eq!(
+            Some("line 1: output without an ls".to_string()),
+            run_commands("dir a").err()
+        );
+    }
+
+    #[test]
+    fn test_plan_cleanup() {
+        let fs = run_commands(EXAMPLE).unwrap();
+        let cleanup = fs.plan_cleanup(70_000_000, 30_000_000).unwrap();
+        assert_eq!(8381165, cleanup.needed);
+        assert_eq!("/d", cleanup.directory.unwrap().path);
+        assert_eq!(1, cleanup.directories.len());
+        assert_eq!(24933642, cleanup.directories[0].size);
+
+        // a, d and / are all big enough, a is the smallest
+        let cleanup = fs.plan_cleanup(48_381_165 + 100, 94_000).unwrap();
+        assert_eq!(93900, cleanup.needed);
+        assert_eq!(Some(94853), cleanup.directory.map(|dir| dir.size));
+
+        let cleanup = fs.plan_cleanup(70_000_000, 1000).unwrap();
+        assert_eq!(None, cleanup.directory);
+        assert!(cleanup.directories.is_empty());
+
+        assert!(fs.plan_cleanup(70_000_000, 70_000_001).is_err());
+        assert!(fs.plan_cleanup(1000, 0).is_err());
+    }
+
+    #[test]
+    fn test_plan_cleanup_set() {
+        // neither x nor y frees enough alone, the only single directory that
+        // does is the root
+        let transcript = indoc! {"
+            $ ls
+            dir x
+            dir y
+            10 z
+            $ cd x
+            $ ls
+            dir w
+            $ cd w
+            $ ls
+            60 v
+            $ cd /y
+            $ ls
+            50 u"};
+        let fs = run_commands(transcript).unwrap();
+        let cleanup = fs.plan_cleanup(150, 120).unwrap();
+        assert_eq!(90, cleanup.needed);
+        assert_eq!("/", cleanup.directory.unwrap().path);
+        let paths: Vec<_> = cleanup
+            .directories
+            .iter()
+            .map(|d| d.path.as_str())
+            .collect();
+        assert_eq!(vec!["/x", "/y"], paths);
+
+        let fs = run_commands(&read_input_file()).unwrap();
+        let cleanup = fs.plan_cleanup(70_000_000, 30_000_000).unwrap();
+        let total: u32 = cleanup.directories.iter().map(|dir| dir.size).sum();
+        assert!(total >= cleanup.needed && total <= 2832508);
+    }
+
+    fn messages(report: &Report) -> Vec<String> {
+        report.problems.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_check_reports_everything() {
+        let transcript = indoc! {"
+            $ cd ..
+            $ ls
+            dir a
+            10 b
+            10 b
+            $ cd a
+            $ ls
+            5 c
+            $ cd /
+            $ ls
+            dir a
+            20 b
+            3 d
+            $ cd a
+            $ ls
+            $ foo
+            dir"};
+
+        let report = check(transcript, Conflicts::Reject);
+        assert_eq!(
+            vec![
+                "line 1: can't go above /",
+                "line 5: /b is listed twice",
+                "line 12: /b is listed as size 20 but was size 10",
+                "line 13: /d wasn't listed before",
+                "line 15: /a/c is missing from the listing",
+                "line 16: unknown command $ foo",
+                "line 17: couldn't parse dir",
+            ],
+            messages(&report)
+        );
+        assert_eq!(Ok(15), report.file_system.du("/"));
+        assert!(report.file_system.du("/d").is_err());
+    }
+
+    #[test]
+    fn test_check_merge() {
+        let transcript = indoc! {"
+            $ ls
+            dir a
+            10 b
+            $ cd a
+            $ ls
+            5 c
+            $ cd /
+            $ ls
+            20 b
+            3 d
+            7 a"};
+
+        let report = check(transcript, Conflicts::Merge);
+        assert_eq!(
+            vec![
+                "line 9: /b is listed as size 20 but was size 10",
+                "line 10: /d wasn't listed before",
+                "line 11: /a is listed as size 7 but was a directory",
+            ],
+            messages(&report)
+        );
+        // newer sizes win, and a stays a directory
+        assert_eq!(Ok(28), report.file_system.du("/"));
+        assert_eq!(Ok(5), report.file_system.du("/a"));
+        assert_eq!(Ok(3), report.file_system.du("/d"));
+        assert_eq!(Ok(20), report.file_system.du("/b"));
+        assert!(check(EXAMPLE, Conflicts::Merge).problems.is_empty());
+    }
 }
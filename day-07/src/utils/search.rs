@@ -0,0 +1,175 @@
+use std::collections::HashSet;
+use std::hash::Hash;
+
+// a node in an optimization search. every state is a candidate answer with its
+// own score, successors are the states we can move to from here, and the bound
+// is an optimistic guess at the best score reachable from this state - the
+// tighter the bound the more of the search tree can be pruned.
+pub trait SearchState: Sized {
+    type Score: Ord + Copy;
+
+    fn successors(&self) -> Vec<Self>;
+    // same as successors but told the best score found so far, for states
+    // that can give up part way through working out their successors once
+    // they know they won't beat it.
+    fn successors_beating(&self, _best: Self::Score) -> Vec<Self> {
+        self.successors()
+    }
+    fn score(&self) -> Self::Score;
+    fn bound(&self) -> Option<Self::Score> {
+        None
+    }
+}
+
+// depth first branch and bound, returns the best scoring state found. any
+// state whose bound can't beat the best score seen so far is not expanded.
+pub fn dfs<S>(start: S) -> S
+where
+    S: SearchState + Clone,
+{
+    search(start, |_| true)
+}
+
+// same as dfs but never expands the same state twice, worth it when many
+// different paths lead to identical states.
+pub fn dfs_memoized<S>(start: S) -> S
+where
+    S: SearchState + Clone + Hash + Eq,
+{
+    let mut seen = HashSet::new();
+    search(start, |state: &S| seen.insert(state.clone()))
+}
+
+// only keeps the most promising `width` states at each depth, ranked by their
+// bound (or score if unbounded). much faster than dfs on wide trees but isn't
+// guaranteed to find the best answer.
+pub fn beam<S>(start: S, width: usize) -> S
+where
+    S: SearchState + Clone,
+{
+    let mut best = start.clone();
+    let mut frontier = vec![start];
+
+    while !frontier.is_empty() {
+        let mut next: Vec<S> = frontier.iter().flat_map(|s| s.successors()).collect();
+        next.sort_by_key(|state| std::cmp::Reverse(rank(state)));
+        next.truncate(width);
+
+        if let Some(candidate) = next.iter().max_by_key(|state| state.score()) {
+            if candidate.score() > best.score() {
+                best = candidate.clone();
+            }
+        }
+        frontier = next;
+    }
+
+    best
+}
+
+fn rank<S: SearchState>(state: &S) -> S::Score {
+    state.bound().unwrap_or_else(|| state.score())
+}
+
+fn search<S, F>(start: S, mut should_expand: F) -> S
+where
+    S: SearchState + Clone,
+    F: FnMut(&S) -> bool,
+{
+    let mut best = start.clone();
+    let mut stack = vec![start];
+
+    while let Some(state) = stack.pop() {
+        if state.score() > best.score() {
+            best = state.clone();
+        }
+
+        if let Some(bound) = state.bound() {
+            if bound <= best.score() {
+                continue;
+            }
+        }
+
+        if !should_expand(&state) {
+            continue;
+        }
+
+        stack.extend(state.successors_beating(best.score()));
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // classic 0/1 knapsack: pick items (weight, value) without exceeding the
+    // capacity, state is the next item to consider plus what we've got so far.
+    static ITEMS: [(u32, u32); 5] = [(12, 4), (2, 2), (1, 1), (1, 2), (4, 10)];
+    static CAPACITY: u32 = 15;
+
+    #[derive(Clone, Debug, Hash, Eq, PartialEq)]
+    struct Knapsack {
+        next: usize,
+        weight: u32,
+        value: u32,
+    }
+
+    impl SearchState for Knapsack {
+        type Score = u32;
+
+        fn successors(&self) -> Vec<Knapsack> {
+            if self.next == ITEMS.len() {
+                return vec![];
+            }
+
+            let (weight, value) = ITEMS[self.next];
+            let mut out = vec![Knapsack {
+                next: self.next + 1,
+                ..*self
+            }];
+            if self.weight + weight <= CAPACITY {
+                out.push(Knapsack {
+                    next: self.next + 1,
+                    weight: self.weight + weight,
+                    value: self.value + value,
+                });
+            }
+            out
+        }
+
+        fn score(&self) -> u32 {
+            self.value
+        }
+
+        fn bound(&self) -> Option<u32> {
+            Some(self.value + ITEMS[self.next..].iter().map(|i| i.1).sum::<u32>())
+        }
+    }
+
+    fn start() -> Knapsack {
+        Knapsack {
+            next: 0,
+            weight: 0,
+            value: 0,
+        }
+    }
+
+    #[test]
+    fn test_dfs() {
+        let best = dfs(start());
+        assert_eq!(15, best.value);
+        assert_eq!(8, best.weight);
+    }
+
+    #[test]
+    fn test_dfs_memoized() {
+        assert_eq!(15, dfs_memoized(start()).value);
+    }
+
+    #[test]
+    fn test_beam() {
+        assert_eq!(15, beam(start(), 8).value);
+        assert!(beam(start(), 1).value <= 15);
+    }
+}
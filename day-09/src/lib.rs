@@ -1,15 +1,17 @@
+pub mod utils;
 use std::collections::HashSet;
+use utils::coord::Coord2;
 
 pub fn part_one(input: &str) -> usize {
-    solve(input, vec![(0, 0); 2])
+    solve(input, vec![Coord2::origin(); 2])
 }
 
 pub fn part_two(input: &str) -> usize {
-    solve(input, vec![(0, 0); 10])
+    solve(input, vec![Coord2::origin(); 10])
 }
 
 fn solve(input: &str, mut rope: Rope) -> usize {
-    let mut visited: HashSet<Coordinate> = HashSet::from_iter(vec![(0, 0)]);
+    let mut visited: HashSet<Coordinate> = HashSet::from_iter(vec![Coord2::origin()]);
 
     input
         .lines()
@@ -18,10 +20,10 @@ fn solve(input: &str, mut rope: Rope) -> usize {
             (command[0], command[1].parse::<i32>().unwrap())
         })
         .for_each(|split_line| match split_line {
-            ("R", num_moves) => move_head(&mut rope, (1, 0), num_moves, &mut visited),
-            ("U", num_moves) => move_head(&mut rope, (0, -1), num_moves, &mut visited),
-            ("D", num_moves) => move_head(&mut rope, (0, 1), num_moves, &mut visited),
-            ("L", num_moves) => move_head(&mut rope, (-1, 0), num_moves, &mut visited),
+            ("R", num_moves) => move_head(&mut rope, Coord2::new([1, 0]), num_moves, &mut visited),
+            ("U", num_moves) => move_head(&mut rope, Coord2::new([0, -1]), num_moves, &mut visited),
+            ("D", num_moves) => move_head(&mut rope, Coord2::new([0, 1]), num_moves, &mut visited),
+            ("L", num_moves) => move_head(&mut rope, Coord2::new([-1, 0]), num_moves, &mut visited),
             _ => panic!("unknown direction {:?}", split_line),
         });
 
@@ -30,22 +32,19 @@ fn solve(input: &str, mut rope: Rope) -> usize {
 
 fn move_head(
     rope: &mut Rope,
-    direction: (i32, i32),
+    direction: Coordinate,
     num_moves: i32,
     visited: &mut HashSet<Coordinate>,
 ) {
     for _ in 0..num_moves {
         for idx in 0..rope.len() {
             match idx {
-                0 => rope[0] = rope[0].move_by(&direction),
+                0 => rope[0] += direction,
                 _ => {
                     let previous = rope[idx - 1];
                     let current = rope[idx];
-                    let delta = previous.delta(&current);
-                    if delta > 1 {
-                        let move_x = (previous.0 - current.0).signum();
-                        let move_y = (previous.1 - current.1).signum();
-                        rope[idx] = current.move_by(&(move_x, move_y));
+                    if previous.chebyshev(&current) > 1 {
+                        rope[idx] = current + (previous - current).signum();
 
                         // track if the tail moves only
                         if idx == (rope.len() - 1) {
@@ -58,25 +57,9 @@ fn move_head(
     }
 }
 
-type Coordinate = (i32, i32);
+type Coordinate = Coord2;
 type Rope = Vec<Coordinate>;
 
-// something that is plottable in a space
-trait Plottable {
-    fn delta(&self, other: &Self) -> i32;
-    fn move_by(&self, distance: &Self) -> Self;
-}
-
-impl Plottable for (i32, i32) {
-    fn delta(&self, other: &Self) -> i32 {
-        (self.0 - other.0).abs().max((self.1 - other.1).abs())
-    }
-
-    fn move_by(&self, distance: &Self) -> Self {
-        ((self.0 + distance.0), (self.1 + distance.1))
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,10 +93,14 @@ mod tests {
 
     #[test]
     fn test_utils() {
-        assert_eq!(1, (0, 0).delta(&(1, 1)));
-        assert_eq!(1, (0, 0).delta(&(0, 1)));
-        assert_eq!(2, (0, 0).delta(&(-2, -1)));
-        assert_eq!((3, 2), (1, 1).move_by(&(2, 1)));
+        let origin = Coord2::origin();
+        assert_eq!(1, origin.chebyshev(&Coord2::new([1, 1])));
+        assert_eq!(1, origin.chebyshev(&Coord2::new([0, 1])));
+        assert_eq!(2, origin.chebyshev(&Coord2::new([-2, -1])));
+        assert_eq!(
+            Coord2::new([3, 2]),
+            Coord2::new([1, 1]) + Coord2::new([2, 1])
+        );
     }
 
     #[test]
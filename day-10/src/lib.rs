@@ -1,69 +1,418 @@
-#[derive(Debug)]
-enum Command {
-    Noop,
-    Add(i32),
+pub mod utils;
+use std::collections::{BTreeMap, BTreeSet};
+use utils::grid::{Coordinate, Grid, Point};
+
+// named registers, anything never written reads as zero
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Registers(BTreeMap<String, i32>);
+
+impl Registers {
+    pub fn get(&self, name: &str) -> i32 {
+        self.0.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, name: &str, value: i32) {
+        self.0.insert(name.to_string(), value);
+    }
 }
 
-type Commands = Vec<Command>;
+// what an instruction does once it's taken all of its cycles
+pub type Execute = fn(&mut Registers, &[i32]);
 
-fn parse(input: &str) -> Commands {
-    input
-        .lines()
-        .map(|line| line.split_whitespace().collect())
-        .flat_map(|line: Vec<_>| match line[0] {
-            "noop" => vec![Command::Noop],
-            "addx" => vec![Command::Noop, Command::Add(line[1].parse::<i32>().unwrap())],
-            _ => panic!("unrecognized command: {}", line[0]),
-        })
-        .collect()
+#[derive(Clone, Debug)]
+pub struct Opcode {
+    pub name: String,
+    pub operands: usize,
+    pub cycles: usize,
+    execute: Execute,
 }
 
-pub fn part_one(input: &str) -> i32 {
-    static BREAKPOINTS: [usize; 6] = [20, 60, 100, 140, 180, 220];
-    let commands = parse(input);
-    let mut signal_strength: i32 = 0;
-    let mut register: i32 = 1;
-
-    for (cycle, command) in commands.iter().enumerate() {
-        if BREAKPOINTS.contains(&(cycle + 1)) {
-            signal_strength += ((cycle + 1) as i32) * register;
+#[derive(Clone, Debug)]
+pub struct Instruction {
+    pub opcode: Opcode,
+    pub operands: Vec<i32>,
+}
+
+// the instructions the cpu understands, looked up by name when parsing
+#[derive(Clone, Debug, Default)]
+pub struct InstructionSet {
+    opcodes: BTreeMap<String, Opcode>,
+}
+
+impl InstructionSet {
+    pub fn new() -> InstructionSet {
+        InstructionSet {
+            opcodes: BTreeMap::new(),
         }
+    }
+
+    // noop and addx, as used by the puzzle
+    pub fn standard() -> InstructionSet {
+        InstructionSet::new().with("noop", 0, 1, |_, _| ()).with(
+            "addx",
+            1,
+            2,
+            |registers, operands| registers.set("x", registers.get("x") + operands[0]),
+        )
+    }
+
+    pub fn with(mut self, name: &str, operands: usize, cycles: usize, execute: Execute) -> Self {
+        assert!(cycles > 0, "{} must take at least one cycle", name);
+        self.opcodes.insert(
+            name.to_string(),
+            Opcode {
+                name: name.to_string(),
+                operands,
+                cycles,
+                execute,
+            },
+        );
+        self
+    }
 
-        match command {
-            Command::Noop => (),
-            Command::Add(amount) => register += amount,
+    pub fn parse(&self, input: &str) -> Result<Vec<Instruction>, String> {
+        input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut words = line.split_whitespace();
+                let name = words.next().unwrap();
+                let opcode = self
+                    .opcodes
+                    .get(name)
+                    .ok_or(format!("unrecognized instruction: {}", name))?;
+                let operands = words
+                    .map(|word| {
+                        word.parse::<i32>()
+                            .map_err(|_| format!("{} isn't a number in '{}'", word, line))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                if operands.len() != opcode.operands {
+                    return Err(format!(
+                        "{} takes {} operand(s) but got {}",
+                        name,
+                        opcode.operands,
+                        operands.len()
+                    ));
+                }
+                Ok(Instruction {
+                    opcode: opcode.clone(),
+                    operands,
+                })
+            })
+            .collect()
+    }
+}
+
+// the state of the cpu during a cycle, before any instruction finishing on
+// that cycle has taken effect.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tick {
+    pub cycle: usize,
+    pub pc: usize,
+    pub registers: Registers,
+}
+
+pub type Watch<'a> = Box<dyn FnMut(&Tick) + 'a>;
+
+pub struct Cpu<'a> {
+    program: Vec<Instruction>,
+    registers: Registers,
+    pc: usize,
+    cycle: usize,
+    // cycles spent so far on the instruction at pc
+    elapsed: usize,
+    breakpoints: BTreeSet<usize>,
+    watches: Vec<Watch<'a>>,
+}
+
+impl<'a> Cpu<'a> {
+    pub fn new(program: Vec<Instruction>) -> Cpu<'a> {
+        Cpu {
+            program,
+            registers: Registers::default(),
+            pc: 0,
+            cycle: 0,
+            elapsed: 0,
+            breakpoints: BTreeSet::new(),
+            watches: vec![],
         }
     }
 
-    signal_strength
+    pub fn with_register(mut self, name: &str, value: i32) -> Self {
+        self.registers.set(name, value);
+        self
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    // the number of cycles completed so far
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    pub fn finished(&self) -> bool {
+        self.pc >= self.program.len()
+    }
+
+    // run stops during these cycles
+    pub fn break_at(&mut self, cycle: usize) {
+        self.breakpoints.insert(cycle);
+    }
+
+    // called with every cycle as it happens
+    pub fn watch<F>(&mut self, hook: F)
+    where
+        F: FnMut(&Tick) + 'a,
+    {
+        self.watches.push(Box::new(hook));
+    }
+
+    // runs a single cycle, none once the program has finished
+    pub fn step(&mut self) -> Option<Tick> {
+        let instruction = self.program.get(self.pc)?;
+        self.cycle += 1;
+        self.elapsed += 1;
+
+        let tick = Tick {
+            cycle: self.cycle,
+            pc: self.pc,
+            registers: self.registers.clone(),
+        };
+        self.watches.iter_mut().for_each(|watch| watch(&tick));
+
+        if self.elapsed == instruction.opcode.cycles {
+            (instruction.opcode.execute)(&mut self.registers, &instruction.operands);
+            self.pc += 1;
+            self.elapsed = 0;
+        }
+
+        Some(tick)
+    }
+
+    // runs until a breakpoint, returning the cycle it stopped during. none
+    // if the program finishes first.
+    pub fn run(&mut self) -> Option<Tick> {
+        while let Some(tick) = self.step() {
+            if self.breakpoints.contains(&tick.cycle) {
+                return Some(tick);
+            }
+        }
+        None
+    }
+
+    // every remaining cycle, ignoring breakpoints
+    pub fn trace(&mut self) -> Vec<Tick> {
+        std::iter::from_fn(|| self.step()).collect()
+    }
 }
 
-pub fn part_two(input: &str) {
-    let commands = parse(input);
-    let mut register: i32 = 1;
-    let mut result = ['X'; 240];
+fn cpu<'a>(input: &str) -> Cpu<'a> {
+    let program = InstructionSet::standard().parse(input).unwrap();
+    Cpu::new(program).with_register("x", 1)
+}
 
-    for (cycle, command) in commands.iter().enumerate() {
-        let horizontal_pos = (cycle as i32) % 40;
-        match (horizontal_pos == register)
-            || (horizontal_pos == register + 1)
-            || (horizontal_pos == register - 1)
-        {
-            true => result[cycle] = '#',
-            false => result[cycle] = '.',
+pub fn part_one(input: &str) -> i32 {
+    let mut cpu = cpu(input);
+    (20..=220).step_by(40).for_each(|cycle| cpu.break_at(cycle));
+
+    std::iter::from_fn(|| cpu.run())
+        .map(|tick| tick.cycle as i32 * tick.registers.get("x"))
+        .sum()
+}
+
+// the 4x6 block letters the crt draws, each glyph's rows one after another
+static FONT: [(char, &str); 17] = [
+    ('A', ".##.#..##..######..##..#"),
+    ('B', "###.#..####.#..##..####."),
+    ('C', ".##.#..##...#...#..#.##."),
+    ('E', "#####...###.#...#...####"),
+    ('F', "#####...###.#...#...#..."),
+    ('G', ".##.#..##...#.###..#.###"),
+    ('H', "#..##..######..##..##..#"),
+    ('I', ".###..#...#...#...#..###"),
+    ('J', "..##...#...#...##..#.##."),
+    ('K', "#..##.#.##..#.#.#.#.#..#"),
+    ('L', "#...#...#...#...#...####"),
+    ('O', ".##.#..##..##..##..#.##."),
+    ('P', "###.#..##..####.#...#..."),
+    ('R', "###.#..##..####.#.#.#..#"),
+    ('S', ".####...#....##....####."),
+    ('U', "#..##..##..##..##..#.##."),
+    ('Z', "####...#..#..#..#...####"),
+];
+static GLYPH_WIDTH: usize = 4;
+static GLYPH_HEIGHT: usize = 6;
+
+#[derive(Debug)]
+struct Pixel {
+    coord: Coordinate,
+    lit: bool,
+}
+
+impl Point for Pixel {
+    fn symbol(&self) -> &str {
+        match self.lit {
+            true => "#",
+            false => ".",
         }
+    }
 
-        match command {
-            Command::Noop => (),
-            Command::Add(amount) => register += amount,
+    fn coord(&self) -> Coordinate {
+        self.coord
+    }
+}
+
+#[derive(Debug)]
+pub struct Screen {
+    pub width: usize,
+    pub height: usize,
+    pixels: Grid<Pixel>,
+}
+
+impl Screen {
+    // every pixel starts off dark
+    pub fn new(width: usize, height: usize) -> Screen {
+        let mut screen = Screen {
+            width,
+            height,
+            pixels: Grid::new(),
+        };
+        for y in 0..height {
+            for x in 0..width {
+                screen.set(x, y, false);
+            }
+        }
+        screen
+    }
+
+    pub fn lit(&self, x: usize, y: usize) -> bool {
+        self.pixels
+            .at(&Coordinate(x as i32, y as i32))
+            .is_some_and(|pixel| pixel.lit)
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, lit: bool) {
+        self.pixels.insert(Pixel {
+            coord: Coordinate(x as i32, y as i32),
+            lit,
+        });
+    }
+
+    // the glyph starting at the column, as #/. rows run together
+    fn glyph(&self, column: usize) -> String {
+        (0..GLYPH_HEIGHT)
+            .flat_map(|y| (column..column + GLYPH_WIDTH).map(move |x| (x, y)))
+            .map(|(x, y)| match x < self.width && self.lit(x, y) {
+                true => '#',
+                false => '.',
+            })
+            .collect()
+    }
+
+    // decodes the letters on the screen, one every five columns. any glyph
+    // not in the font is reported along with what it looks like.
+    pub fn read(&self) -> Result<String, String> {
+        if self.height != GLYPH_HEIGHT {
+            return Err(format!(
+                "letters are {} pixels tall but the screen is {}",
+                GLYPH_HEIGHT, self.height
+            ));
+        }
+
+        let mut letters = String::new();
+        let mut unknown = vec![];
+        for (index, column) in (0..self.width).step_by(GLYPH_WIDTH + 1).enumerate() {
+            let glyph = self.glyph(column);
+            match FONT.iter().find(|(_, pattern)| *pattern == glyph) {
+                Some((letter, _)) => letters.push(*letter),
+                None if !glyph.contains('#') => letters.push(' '),
+                None => unknown.push(format!(
+                    "unknown glyph at letter {}:\n{}",
+                    index + 1,
+                    glyph
+                        .as_bytes()
+                        .chunks(GLYPH_WIDTH)
+                        .map(|row| String::from_utf8_lossy(row))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                )),
+            }
+        }
+
+        match unknown.is_empty() {
+            true => Ok(letters.trim_end().to_string()),
+            false => Err(unknown.join("\n\n")),
         }
     }
+}
+
+impl std::fmt::Display for Screen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.pixels.pretty_print())
+    }
+}
+
+// draws one pixel a cycle, left to right and top to bottom, lighting it if
+// the sprite centred on the x register covers it. once the last pixel is
+// drawn the beam goes back to the top left and starts on the next frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Crt {
+    pub width: usize,
+    pub height: usize,
+    pub sprite_width: usize,
+}
+
+impl Default for Crt {
+    fn default() -> Self {
+        Crt::new(40, 6, 3)
+    }
+}
+
+impl Crt {
+    pub fn new(width: usize, height: usize, sprite_width: usize) -> Crt {
+        Crt {
+            width,
+            height,
+            sprite_width,
+        }
+    }
+
+    // even width sprites have the extra pixel to the right of the register
+    fn covers(&self, register: i32, x: usize) -> bool {
+        let left = register - (self.sprite_width as i32 - 1) / 2;
+        let right = register + self.sprite_width as i32 / 2;
+        self.sprite_width > 0 && (left..=right).contains(&(x as i32))
+    }
+
+    // the screen as it is once all the cycles have been drawn, anything the
+    // beam didn't reach stays dark.
+    pub fn draw<I>(&self, ticks: I) -> Screen
+    where
+        I: IntoIterator<Item = Tick>,
+    {
+        let mut screen = Screen::new(self.width, self.height);
+        let pixels = self.width * self.height;
+        if pixels == 0 {
+            return screen;
+        }
+
+        for tick in ticks {
+            let pixel = (tick.cycle - 1) % pixels;
+            let (x, y) = (pixel % self.width, pixel / self.width);
+            screen.set(x, y, self.covers(tick.registers.get("x"), x));
+        }
 
-    for chunk in result.chunks(40) {
-        println!("{}", chunk.iter().collect::<String>());
+        screen
     }
 }
 
+pub fn part_two(input: &str) -> Screen {
+    Crt::default().draw(cpu(input).trace())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -83,11 +432,142 @@ mod tests {
 
     #[test]
     fn test_part_two_example() {
-        part_two(EXAMPLE_INPUT);
+        let expected = "##..##..##..##..##..##..##..##..##..##..\n\
+                        ###...###...###...###...###...###...###.\n\
+                        ####....####....####....####....####....\n\
+                        #####.....#####.....#####.....#####.....\n\
+                        ######......######......######......####\n\
+                        #######.......#######.......#######.....";
+        let screen = part_two(EXAMPLE_INPUT);
+        assert_eq!(expected, screen.to_string());
+        assert!(screen
+            .read()
+            .unwrap_err()
+            .starts_with("unknown glyph at letter 1:\n##..\n###.\n####\n####\n####\n####"));
     }
 
     #[test]
     fn test_part_two() {
-        part_two(INPUT);
+        assert_eq!(Ok("ELPLZGZL".to_string()), part_two(INPUT).read());
+    }
+
+    #[test]
+    fn test_crt_geometry() {
+        // x sits at 1 for six cycles, then moves to 5 for the last three
+        let ticks = || cpu("noop\naddx 0\nnoop\naddx 4\nnoop\nnoop\nnoop").trace();
+        assert_eq!(
+            "####..##\n........",
+            Crt::new(8, 2, 5).draw(ticks()).to_string()
+        );
+        assert_eq!(".##.\n.#..", Crt::new(4, 2, 2).draw(ticks()).to_string());
+        // the ninth cycle wraps around and redraws the top left pixel
+        assert_eq!(".##.\n##..", Crt::new(4, 2, 3).draw(ticks()).to_string());
+        assert_eq!("...\n.#.", Crt::new(3, 2, 1).draw(ticks()).to_string());
+    }
+
+    #[test]
+    fn test_read() {
+        let mut screen = Screen::new(9, 6);
+        for (y, row) in [
+            "#..#.####",
+            "#..#.#...",
+            "####.###.",
+            "#..#.#...",
+            "#..#.#...",
+            "#..#.#...",
+        ]
+        .iter()
+        .enumerate()
+        {
+            row.chars()
+                .enumerate()
+                .for_each(|(x, c)| screen.set(x, y, c == '#'));
+        }
+        assert_eq!(Ok("HF".to_string()), screen.read());
+
+        screen.set(8, 5, true);
+        assert_eq!(
+            Err("unknown glyph at letter 2:\n####\n#...\n###.\n#...\n#...\n#..#".to_string()),
+            screen.read()
+        );
+        assert_eq!(
+            Err("letters are 6 pixels tall but the screen is 5".to_string()),
+            Screen::new(40, 5).read()
+        );
+    }
+
+    #[test]
+    fn test_cycle_counts() {
+        let mut cpu = cpu("noop\naddx 3\naddx -5");
+        let trace = cpu.trace();
+        assert_eq!(
+            vec![1, 1, 1, 4, 4],
+            trace
+                .iter()
+                .map(|t| t.registers.get("x"))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![0, 1, 1, 2, 2],
+            trace.iter().map(|t| t.pc).collect::<Vec<_>>()
+        );
+        assert_eq!(-1, cpu.registers().get("x"));
+        assert_eq!(5, cpu.cycle());
+        assert!(cpu.finished());
+        assert_eq!(None, cpu.step());
+    }
+
+    #[test]
+    fn test_breakpoints_and_watch() {
+        let mut seen = vec![];
+        {
+            let mut cpu = cpu("noop\naddx 3\naddx -5");
+            cpu.break_at(3);
+            cpu.break_at(9);
+            cpu.watch(|tick| seen.push(tick.cycle));
+
+            let stopped = cpu.run().unwrap();
+            assert_eq!(3, stopped.cycle);
+            assert_eq!(1, stopped.registers.get("x"));
+            assert_eq!(4, cpu.registers().get("x"));
+            assert_eq!(None, cpu.run());
+        }
+        assert_eq!(vec![1, 2, 3, 4, 5], seen);
+    }
+
+    #[test]
+    fn test_custom_instructions() {
+        let instructions = InstructionSet::standard()
+            .with("sety", 1, 1, |registers, operands| {
+                registers.set("y", operands[0])
+            })
+            .with("mulxy", 0, 3, |registers, _| {
+                registers.set("x", registers.get("x") * registers.get("y"))
+            });
+        let program = instructions.parse("addx 2\nsety 4\nmulxy\nnoop").unwrap();
+        let mut cpu = Cpu::new(program).with_register("x", 1);
+
+        let trace = cpu.trace();
+        assert_eq!(7, trace.len());
+        assert_eq!(3, trace[5].registers.get("x"));
+        assert_eq!(12, trace[6].registers.get("x"));
+        assert_eq!(4, cpu.registers().get("y"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let instructions = InstructionSet::standard();
+        assert_eq!(
+            Some("unrecognized instruction: jmp".to_string()),
+            instructions.parse("noop\njmp 4").err()
+        );
+        assert_eq!(
+            Some("addx takes 1 operand(s) but got 0".to_string()),
+            instructions.parse("addx").err()
+        );
+        assert_eq!(
+            Some("x isn't a number in 'addx x'".to_string()),
+            instructions.parse("addx x").err()
+        );
     }
 }
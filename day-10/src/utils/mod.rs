@@ -0,0 +1,3 @@
+pub mod coord;
+pub mod grid;
+pub mod rect;
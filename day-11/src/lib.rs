@@ -1,33 +1,283 @@
+use std::ops::RangeFrom;
+
 #[derive(Debug)]
 struct Monkey {
-    items: Vec<u64>,
-    operation: Operation,
+    items: Vec<Item>,
+    operation: Expr,
     test: u64,
     throw_true: usize,
     throw_false: usize,
     inspections: u64,
 }
 
-#[derive(Debug)]
-enum Operation {
-    Square,
-    Multiply(u64),
-    Sum(u64),
+// items are numbered in the order they're listed, starting with monkey 0's
+#[derive(Clone, Copy, Debug)]
+struct Item {
+    id: usize,
+    worry: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Throw {
+    pub round: usize,
+    pub from: usize,
+    pub to: usize,
+    pub item: usize,
+    // the worry level when inspected, and once the operation and relief have
+    // been applied. both are reduced when the modulus is in use.
+    pub before: u64,
+    pub after: u64,
+}
+
+// told about everything that happens during a game, by default nothing
+pub trait Observer {
+    fn throw(&mut self, _throw: &Throw) {}
+    // how many items each monkey inspected during the round
+    fn round(&mut self, _round: usize, _inspections: &[u64]) {}
+}
+
+impl Observer for () {}
+
+// keeps hold of everything it's told
+#[derive(Clone, Debug, Default)]
+pub struct Recorder {
+    pub throws: Vec<Throw>,
+    pub inspections: Vec<Vec<u64>>,
+}
+
+impl Observer for Recorder {
+    fn throw(&mut self, throw: &Throw) {
+        self.throws.push(*throw);
+    }
+
+    fn round(&mut self, _round: usize, inspections: &[u64]) {
+        self.inspections.push(inspections.to_vec());
+    }
+}
+
+impl Recorder {
+    // every throw of the item, in the order they happened
+    pub fn history(&self, item: usize) -> Vec<&Throw> {
+        self.throws
+            .iter()
+            .filter(|throw| throw.item == item)
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Operator {
+    fn from(symbol: char) -> Option<Operator> {
+        match symbol {
+            '+' => Some(Operator::Add),
+            '-' => Some(Operator::Subtract),
+            '*' => Some(Operator::Multiply),
+            '/' => Some(Operator::Divide),
+            _ => None,
+        }
+    }
+
+    fn symbol(&self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Subtract => '-',
+            Operator::Multiply => '*',
+            Operator::Divide => '/',
+        }
+    }
+
+    // worry levels can't go below zero, so taking away more than there is
+    // just leaves nothing
+    fn apply(&self, left: u64, right: u64) -> Result<u64, String> {
+        match self {
+            Operator::Add => left.checked_add(right),
+            Operator::Subtract => Some(left.saturating_sub(right)),
+            Operator::Multiply => left.checked_mul(right),
+            Operator::Divide => left.checked_div(right),
+        }
+        .ok_or(format!(
+            "can't work out {} {} {}",
+            left,
+            self.symbol(),
+            right
+        ))
+    }
+}
+
+// the right hand side of a monkey's `new = ...` line
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    Old,
+    Number(u64),
+    Operation(Operator, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Token {
+    Old,
+    Number(u64),
+    Operator(Operator),
+    Open,
+    Close,
+}
+
+type Tokens = std::iter::Peekable<std::vec::IntoIter<Token>>;
+
+impl Expr {
+    // the usual precedence, * and / before + and -, with brackets to
+    // override it and everything else left to right.
+    pub fn parse(input: &str) -> Result<Expr, String> {
+        let mut tokens = tokenize(input)?.into_iter().peekable();
+        let expr = Expr::sum(&mut tokens, input)?;
+        match tokens.next() {
+            None => Ok(expr),
+            Some(token) => Err(format!("unexpected {:?} in '{}'", token, input)),
+        }
+    }
+
+    fn sum(tokens: &mut Tokens, input: &str) -> Result<Expr, String> {
+        let mut expr = Expr::product(tokens, input)?;
+        while let Some(Token::Operator(operator @ (Operator::Add | Operator::Subtract))) =
+            tokens.peek().copied()
+        {
+            tokens.next();
+            let right = Expr::product(tokens, input)?;
+            expr = Expr::Operation(operator, Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn product(tokens: &mut Tokens, input: &str) -> Result<Expr, String> {
+        let mut expr = Expr::value(tokens, input)?;
+        while let Some(Token::Operator(operator @ (Operator::Multiply | Operator::Divide))) =
+            tokens.peek().copied()
+        {
+            tokens.next();
+            let right = Expr::value(tokens, input)?;
+            expr = Expr::Operation(operator, Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn value(tokens: &mut Tokens, input: &str) -> Result<Expr, String> {
+        match tokens.next() {
+            Some(Token::Old) => Ok(Expr::Old),
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::Open) => {
+                let expr = Expr::sum(tokens, input)?;
+                match tokens.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err(format!("unclosed bracket in '{}'", input)),
+                }
+            }
+            Some(token) => Err(format!("unexpected {:?} in '{}'", token, input)),
+            None => Err(format!("'{}' ends early", input)),
+        }
+    }
+
+    // whether working modulo the product of the tests gives the same
+    // divisibility answers. that holds for sums and products, but not once
+    // anything is taken away or divided as the real value matters then.
+    pub fn modular(&self) -> bool {
+        match self {
+            Expr::Old | Expr::Number(_) => true,
+            Expr::Operation(Operator::Add | Operator::Multiply, left, right) => {
+                left.modular() && right.modular()
+            }
+            Expr::Operation(..) => false,
+        }
+    }
+
+    // the new worry level, kept below the modulus at every step if there is
+    // one. errors if the worry level would overflow or be divided by zero.
+    pub fn evaluate(&self, old: u64, modulus: Option<u64>) -> Result<u64, String> {
+        let value = match self {
+            Expr::Old => old,
+            Expr::Number(value) => *value,
+            Expr::Operation(operator, left, right) => {
+                let left = left.evaluate(old, modulus)?;
+                let right = right.evaluate(old, modulus)?;
+                match (modulus, operator) {
+                    (Some(modulus), Operator::Add) => {
+                        ((left as u128 + right as u128) % modulus as u128) as u64
+                    }
+                    (Some(modulus), Operator::Multiply) => {
+                        ((left as u128 * right as u128) % modulus as u128) as u64
+                    }
+                    _ => operator.apply(left, right)?,
+                }
+            }
+        };
+        Ok(modulus.map_or(value, |modulus| value % modulus))
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = input.chars().peekable();
+    while let Some(symbol) = chars.next() {
+        let token = match symbol {
+            ' ' => continue,
+            '(' => Token::Open,
+            ')' => Token::Close,
+            '0'..='9' => {
+                let mut number = symbol.to_string();
+                while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                    number.push(digit);
+                }
+                Token::Number(
+                    number
+                        .parse()
+                        .map_err(|_| format!("{} is too big in '{}'", number, input))?,
+                )
+            }
+            'o' if chars.next() == Some('l') && chars.next() == Some('d') => Token::Old,
+            _ => match Operator::from(symbol) {
+                Some(operator) => Token::Operator(operator),
+                None => return Err(format!("unexpected '{}' in '{}'", symbol, input)),
+            },
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
 }
 
 pub fn part_one(input: &str) -> u64 {
-    solve(input, 20, 3)
+    solve(input, 20, 3).unwrap()
 }
 
 pub fn part_two(input: &str) -> u64 {
-    solve(input, 10000, 1)
+    solve(input, 10000, 1).unwrap()
+}
+
+fn solve(input: &str, rounds: usize, div_amount: u64) -> Result<u64, String> {
+    play(input, rounds, div_amount, &mut ())
 }
 
-fn solve(input: &str, rounds: usize, div_amount: u64) -> u64 {
-    let mut monkeys = parse(input);
+// plays the rounds, telling the observer about every throw and the end of
+// every round, and returns the product of the top two inspection counts.
+// errors if the input can't be parsed or an operation can't be worked out.
+pub fn play<O: Observer>(
+    input: &str,
+    rounds: usize,
+    div_amount: u64,
+    observer: &mut O,
+) -> Result<u64, String> {
+    let mut monkeys = parse(input)?;
     let divisors = monkeys.iter().map(|m| m.test).product::<u64>();
+    // dividing by the relief amount doesn't survive the modulus either
+    let modulus =
+        (div_amount == 1 && monkeys.iter().all(|m| m.operation.modular())).then_some(divisors);
 
-    for _round in 0..rounds {
+    for round in 1..=rounds {
+        let mut inspections = Vec::with_capacity(monkeys.len());
         for monkey_idx in 0..monkeys.len() {
             let monkey = monkeys.get_mut(monkey_idx).unwrap();
 
@@ -35,77 +285,109 @@ fn solve(input: &str, rounds: usize, div_amount: u64) -> u64 {
                 .items
                 .iter()
                 .map(|item| {
-                    let worry_level = match monkey.operation {
-                        Operation::Square => (item * item) / div_amount,
-                        Operation::Multiply(amount) => (item * amount) / div_amount,
-                        Operation::Sum(amount) => (item + amount) / div_amount,
+                    let worry_level = monkey
+                        .operation
+                        .evaluate(item.worry, modulus)
+                        .map_err(|error| format!("monkey {}: {}", monkey_idx, error))?
+                        / div_amount;
+                    let target = match worry_level.is_multiple_of(monkey.test) {
+                        true => monkey.throw_true,
+                        false => monkey.throw_false,
                     };
-                    match (worry_level % monkey.test) == 0 {
-                        true => (monkey.throw_true, worry_level % divisors),
-                        false => (monkey.throw_false, worry_level % divisors),
-                    }
+                    Ok((target, *item, worry_level))
                 })
-                .collect();
+                .collect::<Result<_, String>>()?;
 
+            inspections.push(monkey.items.len() as u64);
             monkey.inspections += monkey.items.len() as u64;
             monkey.items.clear();
-            for (target, item) in passes {
-                monkeys[target].items.push(item)
+            for (target, item, worry) in passes {
+                observer.throw(&Throw {
+                    round,
+                    from: monkey_idx,
+                    to: target,
+                    item: item.id,
+                    before: item.worry,
+                    after: worry,
+                });
+                monkeys[target].items.push(Item { worry, ..item })
             }
         }
+        observer.round(round, &inspections);
     }
 
-    monkeys.sort_by(|left, right| right.inspections.cmp(&left.inspections));
-    monkeys.iter().take(2).map(|m| m.inspections).product()
+    monkeys.sort_by_key(|monkey| std::cmp::Reverse(monkey.inspections));
+    Ok(monkeys.iter().take(2).map(|m| m.inspections).product())
 }
 
-fn parse(input: &str) -> Vec<Monkey> {
-    input
+fn parse(input: &str) -> Result<Vec<Monkey>, String> {
+    let mut ids = 0..;
+    let monkeys = input
         .split("\n\n")
-        .map(|monkey_str| {
-            let lines: Vec<_> = monkey_str.split("\n").collect();
-            let items = lines[1]
-                .split_once(": ")
-                .unwrap()
-                .1
-                .split(", ")
-                .map(|item| item.parse::<u64>().unwrap())
-                .collect();
-            let operation = lines[2]
-                .split_once("old ")
-                .unwrap()
-                .1
-                .split_once(' ')
-                .map(|(operator, amount)| match (operator, amount) {
-                    ("*", "old") => Operation::Square,
-                    ("*", _) => Operation::Multiply(amount.parse::<u64>().unwrap()),
-                    ("+", _) => Operation::Sum(amount.parse::<u64>().unwrap()),
-                    _ => panic!("unknown operator: {}", operator),
-                })
-                .unwrap();
-            let test = lines[3]
-                .split_once("divisible by ")
-                .map(|(_, amount)| amount.parse::<u64>().unwrap())
-                .unwrap();
-            let throw_true = lines[4]
-                .split_once("throw to monkey ")
-                .map(|(_, amount)| amount.parse::<usize>().unwrap())
-                .unwrap();
-            let throw_false = lines[5]
-                .split_once("throw to monkey ")
-                .map(|(_, amount)| amount.parse::<usize>().unwrap())
-                .unwrap();
-
-            Monkey {
-                items,
-                operation,
-                test,
-                throw_true,
-                throw_false,
-                inspections: 0,
-            }
+        .enumerate()
+        .map(|(index, monkey_str)| {
+            parse_monkey(monkey_str, &mut ids)
+                .map_err(|error| format!("monkey {}: {}", index, error))
         })
-        .collect()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (index, monkey) in monkeys.iter().enumerate() {
+        if let Some(target) = [monkey.throw_true, monkey.throw_false]
+            .into_iter()
+            .find(|target| *target >= monkeys.len())
+        {
+            return Err(format!("monkey {}: there's no monkey {}", index, target));
+        }
+    }
+    Ok(monkeys)
+}
+
+fn parse_monkey(monkey_str: &str, ids: &mut RangeFrom<usize>) -> Result<Monkey, String> {
+    let lines: Vec<_> = monkey_str.lines().collect();
+    // the rest of the line after the marker
+    let field = |index: usize, marker: &str| {
+        lines
+            .get(index)
+            .and_then(|line| line.split_once(marker))
+            .map(|(_, rest)| rest.trim())
+            .ok_or(format!(
+                "expected '{}' on line {}",
+                marker.trim(),
+                index + 1
+            ))
+    };
+    let number = |value: &str| {
+        value
+            .parse::<u64>()
+            .map_err(|_| format!("{} isn't a number", value))
+    };
+
+    let items = field(1, "items:")?
+        .split(", ")
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            Ok(Item {
+                id: ids.next().unwrap(),
+                worry: number(item)?,
+            })
+        })
+        .collect::<Result<_, String>>()?;
+    let operation = Expr::parse(field(2, "new = ")?)?;
+    let test = number(field(3, "divisible by ")?)?;
+    if test == 0 {
+        return Err("can't test for divisibility by 0".to_string());
+    }
+    let throw_true = number(field(4, "throw to monkey ")?)? as usize;
+    let throw_false = number(field(5, "throw to monkey ")?)? as usize;
+
+    Ok(Monkey {
+        items,
+        operation,
+        test,
+        throw_true,
+        throw_false,
+        inspections: 0,
+    })
 }
 
 #[cfg(test)]
@@ -134,4 +416,201 @@ mod tests {
     fn test_part_two() {
         assert_eq!(28244037010, part_two(INPUT));
     }
+
+    fn number(value: u64) -> Box<Expr> {
+        Box::new(Expr::Number(value))
+    }
+
+    #[test]
+    fn test_parse_expression() {
+        use Operator::*;
+        let square_plus_one = Expr::Operation(
+            Add,
+            Box::new(Expr::Operation(
+                Multiply,
+                Box::new(Expr::Old),
+                Box::new(Expr::Old),
+            )),
+            number(1),
+        );
+        assert_eq!(Ok(square_plus_one.clone()), Expr::parse("old * old + 1"));
+        assert_eq!(Ok(square_plus_one), Expr::parse("(old*old)+1"));
+        assert_eq!(
+            Ok(Expr::Operation(
+                Subtract,
+                Box::new(Expr::Operation(Subtract, Box::new(Expr::Old), number(3))),
+                Box::new(Expr::Operation(Divide, number(4), number(2))),
+            )),
+            Expr::parse("old - 3 - 4 / 2")
+        );
+
+        assert_eq!(
+            Err("unexpected 'x' in 'old * x'".to_string()),
+            Expr::parse("old * x")
+        );
+        assert_eq!(Err("'old +' ends early".to_string()), Expr::parse("old +"));
+        assert_eq!(
+            Err("unclosed bracket in '(old + 1'".to_string()),
+            Expr::parse("(old + 1")
+        );
+        assert_eq!(
+            Err("unexpected Close in 'old)'".to_string()),
+            Expr::parse("old)")
+        );
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let square_plus_one = Expr::parse("old * old + 1").unwrap();
+        assert!(square_plus_one.modular());
+        assert_eq!(Ok(50), square_plus_one.evaluate(7, None));
+        assert_eq!(Ok(2), square_plus_one.evaluate(7, Some(6)));
+        // big enough to overflow unless it's kept small by the modulus
+        assert_eq!(
+            Ok(2),
+            square_plus_one.evaluate(u64::MAX, Some(u64::MAX - 1))
+        );
+        assert!(square_plus_one.evaluate(u64::MAX, None).is_err());
+
+        let halved = Expr::parse("(old - 3) / 2").unwrap();
+        assert!(!halved.modular());
+        assert_eq!(Ok(3), halved.evaluate(10, None));
+        assert_eq!(Ok(0), halved.evaluate(1, None));
+        assert_eq!(
+            Err("can't work out 5 / 0".to_string()),
+            Expr::parse("old / 0").unwrap().evaluate(5, None)
+        );
+    }
+
+    #[test]
+    fn test_rewritten_operations() {
+        // the same operations written differently give the same answers
+        let rewritten = EXAMPLE_INPUT
+            .replace("new = old * 19", "new = (19 * old)")
+            .replace("new = old + 6", "new = old + 8 - 2");
+        assert_eq!(10605, part_one(&rewritten));
+        assert_eq!(
+            2713310158,
+            part_two(&EXAMPLE_INPUT.replace("new = old * 19", "new = old * 19 + 0"))
+        );
+    }
+
+    #[test]
+    fn test_subtraction() {
+        // 1 and 2 drop to 0 and go to monkey 1, which sends them back as 1
+        let transcript = [
+            "Monkey 0:",
+            "  Starting items: 1, 2",
+            "  Operation: new = old - 3",
+            "  Test: divisible by 2",
+            "    If true: throw to monkey 1",
+            "    If false: throw to monkey 1",
+            "",
+            "Monkey 1:",
+            "  Starting items:",
+            "  Operation: new = old + 3",
+            "  Test: divisible by 5",
+            "    If true: throw to monkey 0",
+            "    If false: throw to monkey 0",
+        ]
+        .join("\n");
+        assert_eq!(40 * 40, part_one(&transcript));
+
+        let subtracted = EXAMPLE_INPUT.replace("new = old + 6", "new = old - 3");
+        assert_eq!(Ok(10010), solve(&subtracted, 20, 3));
+        // without the modulus old * old soon overflows
+        let error = solve(&subtracted, 10000, 1).unwrap_err();
+        assert!(error.starts_with("monkey 2: can't work out"), "{}", error);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let bad_operation = EXAMPLE_INPUT.replace("new = old + 6", "new = old ^ 2");
+        assert_eq!(
+            Err("monkey 1: unexpected '^' in 'old ^ 2'".to_string()),
+            solve(&bad_operation, 20, 3)
+        );
+        let bad_target = EXAMPLE_INPUT.replace("throw to monkey 3", "throw to monkey 7");
+        assert_eq!(
+            Err("monkey 0: there's no monkey 7".to_string()),
+            solve(&bad_target, 20, 3)
+        );
+        assert_eq!(
+            Err("monkey 0: expected 'new =' on line 3".to_string()),
+            solve("Monkey 0:\n  Starting items: 1", 20, 3)
+        );
+    }
+
+    #[test]
+    fn test_recorder() {
+        let mut recorder = Recorder::default();
+        assert_eq!(Ok(10605), play(EXAMPLE_INPUT, 20, 3, &mut recorder));
+
+        assert_eq!(20, recorder.inspections.len());
+        assert_eq!(vec![2, 4, 3, 5], recorder.inspections[0]);
+        let totals: Vec<u64> = (0..4)
+            .map(|monkey| recorder.inspections.iter().map(|r| r[monkey]).sum())
+            .collect();
+        assert_eq!(vec![101, 95, 7, 105], totals);
+        assert_eq!(totals.iter().sum::<u64>() as usize, recorder.throws.len());
+
+        // the 79 monkey 0 starts with goes to 3 and then on to 1 in the first round
+        let history = recorder.history(0);
+        assert_eq!(
+            Throw {
+                round: 1,
+                from: 0,
+                to: 3,
+                item: 0,
+                before: 79,
+                after: 500,
+            },
+            *history[0]
+        );
+        assert_eq!(
+            (1, 3, 1, 500, 167),
+            (
+                history[1].round,
+                history[1].from,
+                history[1].to,
+                history[1].before,
+                history[1].after
+            )
+        );
+        assert!(history.windows(2).all(|pair| pair[0].to == pair[1].from));
+
+        // after the first round monkey 1 is holding 2080, 25, 167, 207, 401, 1046
+        let mut held: Vec<_> = recorder
+            .throws
+            .iter()
+            .filter(|throw| throw.round == 1 && throw.to == 1)
+            .map(|throw| throw.after)
+            .collect();
+        held.sort();
+        assert_eq!(vec![25, 167, 207, 401, 1046, 2080], held);
+    }
+
+    #[test]
+    fn test_recorder_with_modulus() {
+        // without relief the worry levels reported are reduced modulo the
+        // product of the tests, 23 * 19 * 13 * 17
+        let mut recorder = Recorder::default();
+        assert_eq!(Ok(99 * 103), play(EXAMPLE_INPUT, 20, 1, &mut recorder));
+
+        let totals: Vec<u64> = (0..4)
+            .map(|monkey| recorder.inspections.iter().map(|r| r[monkey]).sum())
+            .collect();
+        assert_eq!(vec![99, 97, 8, 103], totals);
+        assert!(recorder
+            .throws
+            .iter()
+            .all(|throw| throw.before < 96577 && throw.after < 96577));
+
+        // monkey 2 squares its items, so some of them come out reduced
+        let squared: Vec<&Throw> = recorder.throws.iter().filter(|t| t.from == 2).collect();
+        assert!(squared.iter().any(|t| t.before * t.before >= 96577));
+        assert!(squared
+            .iter()
+            .all(|t| t.after == t.before * t.before % 96577));
+    }
 }
@@ -1,57 +1,48 @@
 pub mod utils;
 use crate::utils::grid::Coordinate;
+use crate::utils::interval::IntervalSet;
+use itertools::Itertools;
 use regex::Regex;
 
+// every position covered by a sensor on the row, less the beacons which are
+// known to sit on that row.
 pub fn part_one(input: &str, row: i32) -> i32 {
-    parse(input)
+    let sensors = parse(input);
+    let beacons = sensors
         .iter()
-        .flat_map(|sensor| sensor.occupied_at_row(row))
-        .fold(Range(i32::MAX, i32::MIN), |acc, range| acc.merge(&range))
-        .size()
+        .map(|sensor| sensor.beacon)
+        .filter(|beacon| beacon.1 == row)
+        .unique()
+        .count() as u128;
+
+    (occupied_at_row(&sensors, row).len() - beacons) as i32
 }
 
 pub fn part_two(input: &str, max_size: i32) -> i64 {
     let sensors = parse(input);
 
-    for row in 0..max_size {
-        let ranges: Vec<_> = sensors
-            .iter()
-            .flat_map(|sensor| sensor.occupied_at_row(row))
-            .collect();
-
-        let potential_gap: Option<i32> = ranges
-            .iter()
-            .flat_map(|range| [range.0 - 1, range.1 + 1])
-            .filter(|gap| gap > &0 && gap < &max_size)
-            .find(|gap| !ranges.iter().any(|range| range.includes(*gap)));
-
-        if let Some(x) = potential_gap {
-            return ((x as i64) * 4_000_000) + (row as i64);
+    for row in 0..=max_size {
+        let free = occupied_at_row(&sensors, row).complement(0, max_size as i64);
+        let gap = free.iter().next();
+        if let Some((x, _)) = gap {
+            return (x * 4_000_000) + (row as i64);
         }
     }
 
     unreachable!("never found the gap")
 }
 
-#[derive(Debug, Copy, Clone)]
-struct Range(i32, i32);
-impl Range {
-    fn size(&self) -> i32 {
-        self.1 - self.0
-    }
-
-    fn includes(&self, value: i32) -> bool {
-        self.0 <= value && self.1 >= value
-    }
-
-    fn merge(&self, other: &Range) -> Range {
-        Range(other.0.min(self.0), other.1.max(self.1))
-    }
+fn occupied_at_row(sensors: &[Sensor], row: i32) -> IntervalSet {
+    sensors
+        .iter()
+        .flat_map(|sensor| sensor.occupied_at_row(row))
+        .collect()
 }
 
 #[derive(Debug)]
 struct Sensor {
     location: Coordinate,
+    beacon: Coordinate,
     scan_distance: i32,
 }
 
@@ -59,6 +50,7 @@ impl Sensor {
     fn new(location: Coordinate, beacon: Coordinate) -> Sensor {
         Sensor {
             location,
+            beacon,
             scan_distance: location.distance(beacon),
         }
     }
@@ -69,15 +61,15 @@ impl Sensor {
         row == row.min(max).max(min)
     }
 
-    fn occupied_at_row(&self, row: i32) -> Option<Range> {
+    fn occupied_at_row(&self, row: i32) -> Option<(i64, i64)> {
         if !self.occupies_row(row) {
             return None;
         }
 
         let distance_to_row = (self.location.1 - row).abs();
-        Some(Range(
-            self.location.0 - self.scan_distance + distance_to_row,
-            self.location.0 + self.scan_distance - distance_to_row,
+        Some((
+            (self.location.0 - self.scan_distance + distance_to_row) as i64,
+            (self.location.0 + self.scan_distance - distance_to_row) as i64,
         ))
     }
 }
@@ -1,6 +1,8 @@
-use pathfinding::prelude::bfs;
+pub mod utils;
 use regex::Regex;
-use std::{cmp::Ordering, collections::HashMap};
+use std::{cmp::Reverse, collections::HashMap};
+use utils::graph::Graph;
+use utils::search::{dfs, SearchState};
 
 static START_VALVE: &str = "AA";
 
@@ -8,217 +10,471 @@ static START_VALVE: &str = "AA";
 struct Valve {
     name: String,
     flow: usize,
-    connections: Vec<String>,
-    distances: HashMap<String, usize>,
 }
-impl Valve {
-    fn record_distance(&mut self, to: String, cost: usize) {
-        self.distances.insert(to, cost);
-    }
+
+// every valve by name, along with the tunnels connecting them
+#[derive(Debug)]
+struct Valves {
+    valves: HashMap<String, Valve>,
+    tunnels: Graph,
 }
+impl Valves {
+    fn at(&self, location: &str) -> &Valve {
+        self.valves.get(location).unwrap()
+    }
 
-#[derive(Debug, Eq, PartialEq)]
-struct PathResult {
-    time_taken: usize,
-    pressure: usize,
-    path: Vec<String>,
-}
-impl Ord for PathResult {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match (
-            self.pressure.cmp(&other.pressure),
-            self.time_taken.cmp(&other.time_taken),
-        ) {
-            (Ordering::Greater, _) => Ordering::Greater,
-            (Ordering::Less, _) => Ordering::Less,
-            (Ordering::Equal, Ordering::Greater) => Ordering::Greater,
-            (Ordering::Equal, Ordering::Less) => Ordering::Less,
-            (Ordering::Equal, Ordering::Equal) => Ordering::Equal,
+    fn pressure_valves(&self) -> Vec<&Valve> {
+        self.valves.values().filter(|v| v.flow > 0).collect()
+    }
+
+    fn tunnel_index(&self, location: &str) -> usize {
+        match self.tunnels.index(location) {
+            Some(index) => index,
+            None => panic!("unknown valve {}", location),
         }
     }
+
+    // every valve visited walking between the two, including both ends
+    fn path(&self, from: &str, to: &str) -> Vec<String> {
+        let path = self
+            .tunnels
+            .shortest_path(self.tunnel_index(from), self.tunnel_index(to));
+
+        match path {
+            Some(value) => value
+                .into_iter()
+                .map(|index| self.tunnels.name(index).to_string())
+                .collect(),
+            None => panic!("expected path {} -> {} but got None", from, to),
+        }
+    }
+}
+
+// a valve being opened by an agent, the minute is the one spent opening it so
+// the valve releases pressure from the following minute onwards.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Opening {
+    pub valve: String,
+    pub minute: usize,
+}
+
+// the valves a single agent opens, in order, and the pressure they release.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AgentRoute {
+    pub pressure: usize,
+    pub openings: Vec<Opening>,
+}
+
+// the best combined result for every agent, agents with nothing worth doing
+// are left with an empty route.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Plan {
+    pub pressure: usize,
+    pub agents: Vec<AgentRoute>,
+}
+
+// what an agent spends a single minute doing
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Action {
+    Move(String),
+    Open(String),
+    Idle,
 }
-impl PartialOrd for PathResult {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+
+// the best plan broken down minute by minute. schedules hold one action per
+// minute for each agent, contributions are the total pressure each opened
+// valve releases in the order they were opened.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Explanation {
+    pub plan: Plan,
+    pub schedules: Vec<Vec<Action>>,
+    pub contributions: Vec<(String, usize)>,
+    flows: HashMap<String, usize>,
+}
+impl Explanation {
+    // renders the schedule in the same style as the puzzle description
+    pub fn timeline(&self) -> String {
+        let minutes = self.schedules.first().map_or(0, |schedule| schedule.len());
+        let mut open: Vec<&String> = vec![];
+        let mut out = vec![];
+
+        for minute in 0..minutes {
+            out.push(format!("== Minute {} ==", minute + 1));
+            let released: usize = open.iter().map(|valve| self.flows[*valve]).sum();
+            let mut names: Vec<&str> = open.iter().map(|valve| valve.as_str()).collect();
+            names.sort();
+            match names.as_slice() {
+                [] => out.push("No valves are open.".to_string()),
+                [name] => out.push(format!(
+                    "Valve {} is open, releasing {} pressure.",
+                    name, released
+                )),
+                [first, second] => out.push(format!(
+                    "Valves {} and {} are open, releasing {} pressure.",
+                    first, second, released
+                )),
+                [rest @ .., last] => out.push(format!(
+                    "Valves {}, and {} are open, releasing {} pressure.",
+                    rest.join(", "),
+                    last,
+                    released
+                )),
+            }
+
+            for (agent, schedule) in self.schedules.iter().enumerate() {
+                match &schedule[minute] {
+                    Action::Move(valve) => {
+                        out.push(format!("Agent {} moves to valve {}.", agent + 1, valve))
+                    }
+                    Action::Open(valve) => {
+                        out.push(format!("Agent {} opens valve {}.", agent + 1, valve));
+                        open.push(valve);
+                    }
+                    Action::Idle => out.push(format!("Agent {} waits.", agent + 1)),
+                }
+            }
+            out.push(String::new());
+        }
+
+        out.join("\n").trim_end().to_string()
     }
 }
 
-#[derive(Debug)]
-struct Valves(HashMap<String, Valve>);
-impl Valves {
-    fn at(&self, location: &str) -> &Valve {
-        self.0.get(location).unwrap()
+pub fn part_one(input: &str) -> usize {
+    solve(input, 1, 30, START_VALVE).pressure
+}
+
+pub fn part_two(input: &str) -> usize {
+    solve(input, 2, 26, START_VALVE).pressure
+}
+
+// each agent opens a disjoint set of valves, so work out the best route for
+// every set a single agent could open in the time, then find the best
+// combination of sets which don't share any valves.
+pub fn solve(input: &str, agents: usize, time_allowed: usize, start: &str) -> Plan {
+    solve_valves(&parse(input), agents, time_allowed, start)
+}
+
+fn solve_valves(valves: &Valves, agents: usize, time_allowed: usize, start: &str) -> Plan {
+    let network = Network::new(valves, start);
+
+    // a single agent doesn't need every set, so we can search for its best
+    // route directly and prune anything which can't beat it.
+    if agents == 1 {
+        let route = dfs(Route::new(&network, time_allowed));
+        return Plan {
+            pressure: route.path.pressure,
+            agents: vec![network.agent_route(&route.path)],
+        };
     }
 
-    fn at_mut(&mut self, location: &str) -> &mut Valve {
-        self.0.get_mut(location).unwrap()
+    let mut subsets: Vec<(u64, SubsetRoute)> =
+        network.best_per_subset(time_allowed).into_iter().collect();
+    subsets.sort_by_key(|(_, route)| Reverse(route.pressure));
+
+    let mut best = (0, vec![]);
+    combine(&subsets, agents, 0, 0, &mut vec![], &mut best);
+
+    let (pressure, chosen) = best;
+    let mut routes: Vec<AgentRoute> = chosen
+        .iter()
+        .map(|index| network.agent_route(&subsets[*index].1))
+        .collect();
+    routes.resize(agents, AgentRoute::default());
+
+    Plan {
+        pressure,
+        agents: routes,
     }
+}
 
-    fn pressure_valves(&self) -> Vec<&Valve> {
-        self.0.values().filter(|v| v.flow > 0).collect()
+// picks up to `agents` disjoint subsets with the highest combined pressure.
+// subsets are sorted from best to worst so each agent only considers subsets
+// after the previous agent's, and we can stop as soon as the remaining agents
+// couldn't beat the best even with the best remaining subset each.
+fn combine(
+    subsets: &[(u64, SubsetRoute)],
+    agents: usize,
+    opened: u64,
+    pressure: usize,
+    chosen: &mut Vec<usize>,
+    best: &mut (usize, Vec<usize>),
+) {
+    if pressure > best.0 {
+        *best = (pressure, chosen.clone());
+    }
+    if agents == 0 {
+        return;
+    }
+
+    let from = chosen.last().map_or(0, |index| index + 1);
+    for (index, (subset, route)) in subsets.iter().enumerate().skip(from) {
+        if pressure + (route.pressure * agents) <= best.0 {
+            break;
+        }
+        if subset & opened == 0 {
+            chosen.push(index);
+            let pressure = pressure + route.pressure;
+            combine(subsets, agents - 1, opened | subset, pressure, chosen, best);
+            chosen.pop();
+        }
     }
 }
 
-pub fn part_one(input: &str) -> usize {
+// solves for the best plan then expands each agent's route into the individual
+// minutes spent walking, opening and idling.
+pub fn explain(input: &str, agents: usize, time_allowed: usize, start: &str) -> Explanation {
     let valves = parse(input);
-    let start = valves.at(START_VALVE);
+    let plan = solve_valves(&valves, agents, time_allowed, start);
 
-    match best_path(start, &valves, 0, 0, vec![], 30) {
-        Some(result) => result.pressure,
-        None => panic!("got None from best_moves"),
+    let schedules = plan
+        .agents
+        .iter()
+        .map(|route| {
+            let mut current = start.to_string();
+            let mut schedule = vec![];
+            for opening in &route.openings {
+                valves
+                    .path(&current, &opening.valve)
+                    .into_iter()
+                    .skip(1)
+                    .for_each(|valve| schedule.push(Action::Move(valve)));
+                schedule.push(Action::Open(opening.valve.to_string()));
+                current = opening.valve.to_string();
+            }
+            schedule.resize(time_allowed, Action::Idle);
+            schedule
+        })
+        .collect();
+
+    let mut openings: Vec<&Opening> = plan.agents.iter().flat_map(|a| &a.openings).collect();
+    openings.sort_by_key(|opening| opening.minute);
+    let contributions = openings
+        .iter()
+        .map(|opening| {
+            let flow = valves.at(&opening.valve).flow;
+            (
+                opening.valve.to_string(),
+                flow * (time_allowed - opening.minute),
+            )
+        })
+        .collect();
+
+    let flows = valves
+        .valves
+        .values()
+        .map(|valve| (valve.name.to_string(), valve.flow))
+        .collect();
+
+    Explanation {
+        plan,
+        schedules,
+        contributions,
+        flows,
     }
 }
 
-pub fn part_two(input: &str) -> usize {
-    let valves = parse(input);
-    let start = valves.at(START_VALVE);
-    let time_allowed = 26;
-    let human_paths = all_paths(start, &valves, 0, 0, vec![], time_allowed);
-    let mut best_pressure = 0;
-
-    for human_path in human_paths {
-        let elephant_paths = all_paths(
-            start,
-            &valves,
-            0,
-            human_path.pressure,
-            human_path.path,
-            time_allowed,
+// best route found for a set of valves, openings are (valve index, minute)
+#[derive(Clone, Debug, Default)]
+struct SubsetRoute {
+    pressure: usize,
+    openings: Vec<(usize, usize)>,
+}
+
+// the valves worth opening, indexed so that any set of them can be stored as
+// a bitmask, along with the distances between them and from the start valve.
+#[derive(Debug)]
+struct Network {
+    names: Vec<String>,
+    flows: Vec<usize>,
+    distances: Vec<Vec<usize>>,
+    start_distances: Vec<usize>,
+}
+impl Network {
+    fn new(valves: &Valves, start: &str) -> Network {
+        let mut names: Vec<String> = valves
+            .pressure_valves()
+            .into_iter()
+            .map(|valve| valve.name.to_string())
+            .collect();
+        names.sort();
+        assert!(
+            names.len() <= 64,
+            "too many pressure valves for a u64 bitmask"
         );
-        for elephant_path in elephant_paths {
-            best_pressure = best_pressure.max(elephant_path.pressure);
+
+        // squash the tunnels down to just the pressure valves, with the start
+        // valve tacked onto the end
+        let keep: Vec<usize> = names
+            .iter()
+            .map(|name| name.as_str())
+            .chain([start])
+            .map(|name| valves.tunnel_index(name))
+            .collect();
+        let compressed = valves.tunnels.compress(&keep);
+        let distance = |from: usize, to: usize| match compressed[from][to] {
+            Some(distance) => distance,
+            None => panic!(
+                "expected path {} -> {} but got None",
+                valves.tunnels.name(keep[from]),
+                valves.tunnels.name(keep[to])
+            ),
+        };
+
+        Network {
+            flows: names.iter().map(|name| valves.at(name).flow).collect(),
+            distances: (0..names.len())
+                .map(|from| (0..names.len()).map(|to| distance(from, to)).collect())
+                .collect(),
+            start_distances: (0..names.len())
+                .map(|to| distance(names.len(), to))
+                .collect(),
+            names,
         }
     }
 
-    best_pressure
-}
+    // the best route a single agent can take to open exactly the valves in
+    // each set. we want an answer for every set rather than one overall, so
+    // this walks every route without pruning.
+    fn best_per_subset(&self, time_allowed: usize) -> HashMap<u64, SubsetRoute> {
+        let mut best = HashMap::from([(0, SubsetRoute::default())]);
+        let mut stack = vec![Route::new(self, time_allowed)];
+
+        while let Some(route) = stack.pop() {
+            for (next, time_taken) in route.reachable() {
+                let child = route.open(next, time_taken);
+                let entry = best.entry(child.opened).or_default();
+                if child.path.pressure > entry.pressure {
+                    *entry = child.path.clone();
+                }
+                stack.push(child);
+            }
+        }
 
-fn best_path(
-    current: &Valve,
-    valves: &Valves,
-    time_taken: usize,
-    pressure: usize,
-    path: Vec<String>,
-    time_allowed: usize,
-) -> Option<PathResult> {
-    all_paths(current, valves, time_taken, pressure, path, time_allowed)
-        .into_iter()
-        .max()
+        best
+    }
+
+    fn agent_route(&self, route: &SubsetRoute) -> AgentRoute {
+        AgentRoute {
+            pressure: route.pressure,
+            openings: route
+                .openings
+                .iter()
+                .map(|(valve, minute)| Opening {
+                    valve: self.names[*valve].to_string(),
+                    minute: *minute,
+                })
+                .collect(),
+        }
+    }
 }
 
-fn all_paths(
-    current: &Valve,
-    valves: &Valves,
+// a single agent opening valves one after another, starting from the start
+// valve. the path holds every valve opened so far along with the pressure
+// they'll release.
+#[derive(Clone, Debug)]
+struct Route<'a> {
+    network: &'a Network,
+    current: Option<usize>,
     time_taken: usize,
-    pressure: usize,
-    path: Vec<String>,
+    opened: u64,
+    path: SubsetRoute,
     time_allowed: usize,
-) -> Vec<PathResult> {
-    let mut out = vec![];
+}
+impl<'a> Route<'a> {
+    fn new(network: &'a Network, time_allowed: usize) -> Route<'a> {
+        Route {
+            network,
+            current: None,
+            time_taken: 0,
+            opened: 0,
+            path: SubsetRoute::default(),
+            time_allowed,
+        }
+    }
 
-    // don't insert the start node as a path we travel to
-    if time_taken != 0 {
-        out.push(PathResult {
-            time_taken,
-            pressure,
-            path: path.clone(),
-        });
+    // valves we could still open along with the minute we'd open them at,
+    // opening one in the final minute wouldn't release anything.
+    fn reachable(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let network = self.network;
+        (0..network.flows.len())
+            .filter(|next| self.opened & (1 << next) == 0)
+            .map(|next| {
+                let distance = match self.current {
+                    Some(valve) => network.distances[valve][next],
+                    None => network.start_distances[next],
+                };
+                (next, self.time_taken + distance + 1)
+            })
+            .filter(|(_, time_taken)| *time_taken < self.time_allowed)
     }
 
-    for (child_str, distance) in current.distances.iter() {
-        let child = valves.at(child_str);
-        let time_taken = time_taken + distance + 1;
+    fn open(&self, next: usize, time_taken: usize) -> Route<'a> {
+        let mut path = self.path.clone();
+        path.pressure += (self.time_allowed - time_taken) * self.network.flows[next];
+        path.openings.push((next, time_taken));
 
-        // if we've already traveled to this child previously, it doesn't make
-        // sense to revisit as it's already on
-        if path.contains(child_str) {
-            continue;
+        Route {
+            current: Some(next),
+            time_taken,
+            opened: self.opened | (1 << next),
+            path,
+            ..*self
         }
+    }
+}
+impl SearchState for Route<'_> {
+    type Score = usize;
 
-        // if we're going to exceed our max time, then we can't travel to this child
-        if time_taken > time_allowed {
-            continue;
-        }
+    fn successors(&self) -> Vec<Self> {
+        self.reachable()
+            .map(|(next, time_taken)| self.open(next, time_taken))
+            .collect()
+    }
 
-        let mut child_path = path.clone();
-        child_path.push(child_str.to_string());
+    fn score(&self) -> usize {
+        self.path.pressure
+    }
 
-        let child_pressure = pressure + ((time_allowed - time_taken) * child.flow);
-        out.extend(all_paths(
-            child,
-            valves,
-            time_taken,
-            child_pressure,
-            child_path,
-            time_allowed,
-        ))
+    // as if we could walk directly from here to every remaining valve
+    fn bound(&self) -> Option<usize> {
+        let remaining: usize = self
+            .reachable()
+            .map(|(next, time_taken)| (self.time_allowed - time_taken) * self.network.flows[next])
+            .sum();
+        Some(self.path.pressure + remaining)
     }
-    out
 }
 
 // parses our input structure into a hashmap where the name is the name of the
-// valve for easier lookup.s
+// valve for easier lookup, and a graph of the tunnels between them.
 fn parse(input: &str) -> Valves {
     let pattern = r"Valve ([A-Z]{2}) has flow rate=(\d+); tunnels? leads? to valves? (.*)";
     let regex = Regex::new(pattern).unwrap();
-    let mut valves = Valves(
-        input
-            .lines()
-            .flat_map(|line| {
-                regex.captures_iter(line).map(|cap| Valve {
-                    name: cap[1].to_string(),
-                    flow: cap[2].parse().unwrap(),
-                    connections: cap[3].split(',').map(|v| v.trim().to_string()).collect(),
-                    distances: HashMap::new(),
-                })
-            })
-            .map(|valve| (valve.name.to_string(), valve))
-            .collect(),
-    );
-
-    combinations(valves.pressure_valves())
-        .iter()
-        .for_each(|(from, to)| {
-            let distance = measure_distance(&valves, from, to);
-            let from_valve = valves.at_mut(from);
-            from_valve.record_distance(to.to_string(), distance);
-
-            let to_valve = valves.at_mut(to);
-            to_valve.record_distance(from.to_string(), distance);
+    let mut valves = HashMap::new();
+    let mut tunnels = Graph::new();
+
+    input
+        .lines()
+        .flat_map(|line| regex.captures_iter(line))
+        .for_each(|cap| {
+            let name = cap[1].to_string();
+            tunnels.intern(&name);
+            cap[3]
+                .split(',')
+                .for_each(|to| tunnels.add_edge(&name, to.trim()));
+
+            let flow = cap[2].parse().unwrap();
+            valves.insert(name.to_string(), Valve { name, flow });
         });
 
-    valves
-}
-
-// calculates the movement distance between two coordinates
-fn measure_distance(valves: &Valves, from: &String, to: &String) -> usize {
-    let path = bfs(
-        from,
-        |source| valves.at(source).connections.clone(),
-        |node| node == to,
-    );
-
-    match path {
-        Some(value) => value.len() - 1,
-        None => panic!("expected path {} -> {} but got None", from, to),
-    }
-}
-
-// returns all the possible combinations between strings provided
-fn combinations(valves: Vec<&Valve>) -> Vec<(String, String)> {
-    let mut combinations = Vec::new();
-    for i in 0..valves.len() {
-        combinations.push((START_VALVE.to_string(), valves[i].name.clone()));
-        for j in i + 1..valves.len() {
-            combinations.push((valves[i].name.clone(), valves[j].name.clone()));
-        }
-    }
-    combinations
+    Valves { valves, tunnels }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use itertools::Itertools;
 
     static EXAMPLE_INPUT: &str = include_str!("../input.example.txt");
     static INPUT: &str = include_str!("../input.txt");
@@ -233,6 +489,86 @@ mod tests {
         assert_eq!(2080, part_one(INPUT));
     }
 
+    #[test]
+    fn test_solve_single_agent_route() {
+        let plan = solve(EXAMPLE_INPUT, 1, 30, START_VALVE);
+        let openings: Vec<_> = plan.agents[0]
+            .openings
+            .iter()
+            .map(|opening| (opening.valve.as_str(), opening.minute))
+            .collect();
+        assert_eq!(
+            vec![
+                ("DD", 2),
+                ("BB", 5),
+                ("JJ", 9),
+                ("HH", 17),
+                ("EE", 21),
+                ("CC", 24)
+            ],
+            openings
+        );
+    }
+
+    #[test]
+    fn test_solve_many_agents() {
+        let plan = solve(EXAMPLE_INPUT, 2, 26, START_VALVE);
+        assert_eq!(1707, plan.pressure);
+        assert_eq!(
+            plan.pressure,
+            plan.agents.iter().map(|agent| agent.pressure).sum()
+        );
+
+        // more agents than there are valves worth opening, some will idle
+        let plan = solve(EXAMPLE_INPUT, 8, 26, START_VALVE);
+        assert_eq!(8, plan.agents.len());
+        assert!(plan.pressure >= 1707);
+        assert!(plan.agents.iter().any(|agent| agent.openings.is_empty()));
+
+        let opened: Vec<_> = plan
+            .agents
+            .iter()
+            .flat_map(|agent| agent.openings.iter().map(|o| o.valve.as_str()))
+            .collect();
+        assert_eq!(opened.len(), opened.iter().unique().count());
+    }
+
+    #[test]
+    fn test_solve_start_and_time() {
+        assert_eq!(0, solve(EXAMPLE_INPUT, 1, 1, START_VALVE).pressure);
+        assert_eq!(20, solve(EXAMPLE_INPUT, 1, 2, "DD").pressure);
+    }
+
+    #[test]
+    fn test_explain() {
+        let explanation = explain(EXAMPLE_INPUT, 1, 30, START_VALVE);
+        let schedule = &explanation.schedules[0];
+        assert_eq!(30, schedule.len());
+        assert_eq!(Action::Move("DD".to_string()), schedule[0]);
+        assert_eq!(Action::Open("DD".to_string()), schedule[1]);
+        assert_eq!(Action::Move("CC".to_string()), schedule[2]);
+        assert_eq!(Action::Open("CC".to_string()), schedule[23]);
+        assert_eq!(Action::Idle, schedule[24]);
+
+        assert_eq!(("DD".to_string(), 560), explanation.contributions[0]);
+        assert_eq!(
+            1651,
+            explanation.contributions.iter().map(|c| c.1).sum::<usize>()
+        );
+
+        let timeline = explanation.timeline();
+        assert!(
+            timeline.starts_with("== Minute 1 ==\nNo valves are open.\nAgent 1 moves to valve DD.")
+        );
+        assert!(timeline.contains("== Minute 3 ==\nValve DD is open, releasing 20 pressure."));
+        assert!(
+            timeline.contains("== Minute 6 ==\nValves BB and DD are open, releasing 33 pressure.")
+        );
+        assert!(timeline
+            .contains("== Minute 10 ==\nValves BB, DD, and JJ are open, releasing 54 pressure."));
+        assert!(timeline.ends_with("releasing 81 pressure.\nAgent 1 waits."));
+    }
+
     #[test]
     fn test_part_two_example() {
         assert_eq!(1707, part_two(EXAMPLE_INPUT));
@@ -240,6 +576,6 @@ mod tests {
 
     #[test]
     fn test_part_two() {
-        // assert_eq!(2752, part_two(INPUT));
+        assert_eq!(2752, part_two(INPUT));
     }
 }
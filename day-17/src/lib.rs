@@ -1,10 +1,10 @@
 pub mod utils;
 
+use crate::utils::cycle::CycleDetector;
 use crate::utils::grid::directions::{EAST, SOUTH, WEST};
 use crate::utils::grid::{BasicPoint, Coordinate, Direction, Grid};
 
 const STARTING_GRID: &str = "-------";
-const LOOP_DETECTOR_WARMUP_SIZE: usize = 200;
 
 #[derive(Copy, Clone, Eq, Hash, PartialEq, Debug)]
 enum Shape {
@@ -145,62 +145,29 @@ impl MoveInstructions {
     }
 }
 
-#[derive(Debug)]
-struct LoopDetector {
-    entries: Vec<LoopCacheEntry>,
-    recordings: usize,
+// everything needed to know how the rest of the simulation will play out: the
+// next shape to drop, the next jet to fire and the shape of the top of the
+// tower.
+type TowerState = (Shape, usize, [i32; 7]);
+
+fn tower_state(shape: Shape, directions: &MoveInstructions, grid: &Grid<BasicPoint>) -> TowerState {
+    (shape, directions.index(), surface(grid))
 }
-impl LoopDetector {
-    fn new() -> LoopDetector {
-        LoopDetector {
-            entries: vec![],
-            recordings: 0,
-        }
-    }
 
-    fn record_rock(
-        &mut self,
-        shape: Shape,
-        total_rocks: i64,
-        movement_index: usize,
-        height: i32,
-    ) -> Option<(i64, i32)> {
-        if shape == Shape::HorizontalLine {
-            self.recordings += 1;
-            if self.recordings > LOOP_DETECTOR_WARMUP_SIZE {
-                self.entries.push(LoopCacheEntry {
-                    total_rocks,
-                    movement_index,
-                    height,
-                });
-
-                // check to see if we've already record an entry for this shape at
-                // this move index, if we recorded more than 3 then check for
-                // the loop
-                let same_movements: Vec<_> = self
-                    .entries
-                    .iter()
-                    .filter(|entry| entry.movement_index == movement_index)
-                    .collect();
-                if same_movements.len() >= 2 {
-                    let first = same_movements.get(0).unwrap();
-                    let second = same_movements.get(1).unwrap();
-                    return Some((
-                        second.total_rocks - first.total_rocks,
-                        second.height - first.height,
-                    ));
-                }
-            }
-        }
-        None
+// depth of the highest rock in each column relative to the top of the tower
+fn surface(grid: &Grid<BasicPoint>) -> [i32; 7] {
+    let mut surface = [0; 7];
+    for (x, depth) in surface.iter_mut().enumerate() {
+        *depth = (grid.min_height..)
+            .find(|y| grid.at(&Coordinate(x as i32, *y)).is_some())
+            .expect("expected floor under every column")
+            - grid.min_height;
     }
+    surface
 }
 
-#[derive(Debug)]
-struct LoopCacheEntry {
-    total_rocks: i64,
-    movement_index: usize,
-    height: i32,
+fn height(grid: &Grid<BasicPoint>) -> i64 {
+    grid.min_height.abs() as i64
 }
 
 pub fn part_one(input: &str) -> i64 {
@@ -216,8 +183,8 @@ fn simulate(input: &str, max_rocks: i64) -> i64 {
     let mut grid = Grid::from(STARTING_GRID, BasicPoint::new);
     let mut rock_counter = 0;
     let mut current_shape = Shape::HorizontalLine.instance(&starting_coordinate(&grid));
-    let mut loop_detector = LoopDetector::new();
-    let mut loop_adjusted_height: i64 = 0;
+    let mut cycle_detector = CycleDetector::new();
+    cycle_detector.record(tower_state(current_shape.1, &directions, &grid), 0);
 
     loop {
         if rock_counter >= max_rocks {
@@ -247,29 +214,18 @@ fn simulate(input: &str, max_rocks: i64) -> i64 {
                 grid.insert(BasicPoint::new(*coordinate, '#'));
             });
             rock_counter += 1;
+            directions.reset(); // ensure our next direction is left/right
 
-            if let Some((every_rocks, increased_amount)) = loop_detector.record_rock(
-                current_shape.1,
-                rock_counter,
-                directions.index(),
-                grid.min_height.abs(),
-            ) {
-                // at this point we know we can fast forward based on the loop
-                // metrics returned to us, ignore if we've already done so
-                if loop_adjusted_height == 0 {
-                    let rocks_remaining = max_rocks - rock_counter;
-                    let fast_forward_loops = rocks_remaining / every_rocks;
-
-                    rock_counter += fast_forward_loops * every_rocks;
-                    loop_adjusted_height = (increased_amount as i64) * fast_forward_loops;
-                }
+            // once we've seen the same state twice the tower will grow the
+            // same way forever, so we can skip straight to the answer
+            let shape = current_shape.next_shape();
+            if let Some(cycle) =
+                cycle_detector.record(tower_state(shape, &directions, &grid), height(&grid))
+            {
+                return cycle_detector.value_at(&cycle, max_rocks as usize);
             }
 
-            current_shape = current_shape
-                .next_shape()
-                .instance(&starting_coordinate(&grid));
-
-            directions.reset(); // ensure our next direction is left/right
+            current_shape = shape.instance(&starting_coordinate(&grid));
             continue;
         }
 
@@ -277,7 +233,7 @@ fn simulate(input: &str, max_rocks: i64) -> i64 {
         // we intersect
         current_shape = next_shape;
     }
-    (grid.min_height.abs() as i64) + loop_adjusted_height
+    height(&grid)
 }
 
 fn starting_coordinate(grid: &Grid<BasicPoint>) -> Coordinate {
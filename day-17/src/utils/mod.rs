@@ -1 +1,2 @@
+pub mod cycle;
 pub mod grid;
@@ -1,46 +1,42 @@
-use std::{cmp::Ordering, collections::HashSet};
+pub mod utils;
+use std::collections::HashSet;
+use utils::coord::Coord3;
+use utils::rect::Cuboid;
 
-#[derive(Debug, Hash, Clone, Copy, Eq, PartialEq)]
-struct Coordinate(i32, i32, i32); // x, y, z
-impl Coordinate {
-    fn from_input(input: &str) -> HashSet<Coordinate> {
-        input
-            .lines()
-            .map(|line| line.split(','))
-            .map(|mut split| {
-                Coordinate(
-                    split.next().unwrap().parse().unwrap(),
-                    split.next().unwrap().parse().unwrap(),
-                    split.next().unwrap().parse().unwrap(),
-                )
-            })
-            .collect()
-    }
+type Coordinate = Coord3; // x, y, z
 
-    // returns a set of "neighbor" coordinates which are not included in the
-    // provided coordinates set.
-    fn empty_neighbors(&self, coordinates: &HashSet<Coordinate>) -> HashSet<Coordinate> {
-        [
-            (1, 0, 0),  // right
-            (-1, 0, 0), // left
-            (0, 1, 0),  // south
-            (0, -1, 0), // north
-            (0, 0, 1),  // towards
-            (0, 0, -1), // away
-        ]
-        .map(|(x, y, z)| Coordinate(self.0 + x, self.1 + y, self.2 + z))
-        .into_iter()
-        .filter(|coordinate| !coordinates.contains(coordinate))
+fn from_input(input: &str) -> HashSet<Coordinate> {
+    input
+        .lines()
+        .map(|line| line.split(','))
+        .map(|mut split| {
+            Coord3::new([
+                split.next().unwrap().parse().unwrap(),
+                split.next().unwrap().parse().unwrap(),
+                split.next().unwrap().parse().unwrap(),
+            ])
+        })
+        .collect()
+}
+
+// returns a set of face sharing "neighbor" coordinates which are not included
+// in the provided coordinates set.
+fn empty_neighbors(
+    coordinate: &Coordinate,
+    coordinates: &HashSet<Coordinate>,
+) -> HashSet<Coordinate> {
+    coordinate
+        .orthogonal_neighbors()
+        .filter(|neighbor| !coordinates.contains(neighbor))
         .collect()
-    }
 }
 
 // parse all coordinates, retrieve all empty neighbors, then sum them up. simple.
 pub fn part_one(input: &str) -> i32 {
-    let coordinates = Coordinate::from_input(input);
+    let coordinates = from_input(input);
     coordinates
         .iter()
-        .map(|coordinate| coordinate.empty_neighbors(&coordinates).len() as i32)
+        .map(|coordinate| empty_neighbors(coordinate, &coordinates).len() as i32)
         .sum()
 }
 
@@ -50,31 +46,17 @@ pub fn part_one(input: &str) -> i32 {
 // system instead. once we've filled in, we can count empty neighbors like part
 // 1 and cross check against the flood fill.
 pub fn part_two(input: &str) -> i32 {
-    let coordinates = Coordinate::from_input(input);
-    let max_x = max_coordinate(&coordinates, |l, r| l.0.cmp(&r.0)).0 + 1;
-    let min_x = max_coordinate(&coordinates, |l, r| r.0.cmp(&l.0)).0 - 1;
-    let max_y = max_coordinate(&coordinates, |l, r| l.1.cmp(&r.1)).1 + 1;
-    let min_y = max_coordinate(&coordinates, |l, r| r.1.cmp(&l.1)).1 - 1;
-    let max_z = max_coordinate(&coordinates, |l, r| l.2.cmp(&r.2)).2 + 1;
-    let min_z = max_coordinate(&coordinates, |l, r| r.2.cmp(&l.2)).2 - 1;
+    let coordinates = from_input(input);
+    let bounds = Cuboid::from_points(coordinates.iter().copied()).expand(1);
 
     let mut to_flood: Vec<Coordinate> = Vec::new(); // all the coordinates we need to process
     let mut flooded: HashSet<Coordinate> = HashSet::new(); // everything we've already marked as flooded
-    to_flood.push(Coordinate(max_x, max_y, max_z));
+    to_flood.push(Coord3::new([bounds.max_x, bounds.max_y, bounds.max_z]));
 
     while let Some(current) = to_flood.pop() {
-        current
-            .empty_neighbors(&coordinates)
+        empty_neighbors(&current, &coordinates)
             .iter()
-            .filter(|coordinate| {
-                !flooded.contains(coordinate)
-                    && coordinate.0 <= max_x
-                    && coordinate.0 >= min_x
-                    && coordinate.1 <= max_y
-                    && coordinate.1 >= min_y
-                    && coordinate.2 <= max_z
-                    && coordinate.2 >= min_z
-            })
+            .filter(|coordinate| !flooded.contains(coordinate) && bounds.contains(**coordinate))
             .for_each(|coord| to_flood.push(*coord));
 
         flooded.insert(current);
@@ -83,8 +65,7 @@ pub fn part_two(input: &str) -> i32 {
     coordinates
         .iter()
         .map(|coordinate| {
-            coordinate
-                .empty_neighbors(&coordinates)
+            empty_neighbors(coordinate, &coordinates)
                 .iter()
                 .filter(|coordinate| flooded.contains(coordinate))
                 .count() as i32
@@ -92,16 +73,6 @@ pub fn part_two(input: &str) -> i32 {
         .sum()
 }
 
-fn max_coordinate<F>(coordinates: &HashSet<Coordinate>, compare: F) -> Coordinate
-where
-    F: Fn(&Coordinate, &Coordinate) -> Ordering,
-{
-    *coordinates
-        .iter()
-        .max_by(|left, right| compare(left, right))
-        .unwrap()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
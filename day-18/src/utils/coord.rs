@@ -0,0 +1,225 @@
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+
+// integer coordinate in any number of dimensions, used as both a position and
+// a vector - Coord<2> for grids, Coord<3> for voxels and so on.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Coord<const N: usize>(pub [i32; N]);
+
+pub type Coord2 = Coord<2>;
+pub type Coord3 = Coord<3>;
+
+impl<const N: usize> Coord<N> {
+    pub const fn new(values: [i32; N]) -> Coord<N> {
+        Coord(values)
+    }
+
+    pub const fn origin() -> Coord<N> {
+        Coord([0; N])
+    }
+
+    // sum of the distance along each axis, aka taxicab distance
+    pub fn manhattan(&self, other: &Coord<N>) -> i32 {
+        (0..N).map(|axis| (self[axis] - other[axis]).abs()).sum()
+    }
+
+    // largest distance along any single axis, aka king's move distance
+    pub fn chebyshev(&self, other: &Coord<N>) -> i32 {
+        (0..N)
+            .map(|axis| (self[axis] - other[axis]).abs())
+            .max()
+            .unwrap_or(0)
+    }
+
+    // clamps every axis to -1, 0 or 1 - a single step in this vector's direction
+    pub fn signum(&self) -> Coord<N> {
+        Coord(self.0.map(|value| value.signum()))
+    }
+
+    pub fn abs(&self) -> Coord<N> {
+        Coord(self.0.map(|value| value.abs()))
+    }
+
+    // one step along each axis in each direction, 2 * N vectors in total
+    pub fn unit_vectors() -> impl Iterator<Item = Coord<N>> {
+        (0..N).flat_map(|axis| {
+            [1, -1].map(|step| {
+                let mut unit = Coord::origin();
+                unit[axis] = step;
+                unit
+            })
+        })
+    }
+
+    // every vector with each axis in -1..=1 other than the origin, 3^N - 1 in total
+    pub fn adjacent_vectors() -> impl Iterator<Item = Coord<N>> {
+        (0..3_usize.pow(N as u32))
+            .map(|mut index| {
+                let mut vector = Coord::origin();
+                for axis in 0..N {
+                    vector[axis] = (index % 3) as i32 - 1;
+                    index /= 3;
+                }
+                vector
+            })
+            .filter(|vector| *vector != Coord::origin())
+    }
+
+    // neighbors which share a face, ie: no diagonals
+    pub fn orthogonal_neighbors(&self) -> impl Iterator<Item = Coord<N>> {
+        let origin = *self;
+        Coord::unit_vectors().map(move |unit| origin + unit)
+    }
+
+    // neighbors which share a face, edge or corner
+    pub fn neighbors(&self) -> impl Iterator<Item = Coord<N>> {
+        let origin = *self;
+        Coord::adjacent_vectors().map(move |vector| origin + vector)
+    }
+}
+
+impl<const N: usize> Default for Coord<N> {
+    fn default() -> Self {
+        Coord::origin()
+    }
+}
+
+impl<const N: usize> From<[i32; N]> for Coord<N> {
+    fn from(values: [i32; N]) -> Self {
+        Coord(values)
+    }
+}
+
+// tuples are what rect and cuboid work in, so coords can be handed straight
+// to them.
+impl From<Coord2> for (i32, i32) {
+    fn from(coord: Coord2) -> Self {
+        (coord[0], coord[1])
+    }
+}
+
+impl From<Coord3> for (i32, i32, i32) {
+    fn from(coord: Coord3) -> Self {
+        (coord[0], coord[1], coord[2])
+    }
+}
+
+impl<const N: usize> Index<usize> for Coord<N> {
+    type Output = i32;
+    fn index(&self, axis: usize) -> &i32 {
+        &self.0[axis]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for Coord<N> {
+    fn index_mut(&mut self, axis: usize) -> &mut i32 {
+        &mut self.0[axis]
+    }
+}
+
+impl<const N: usize> Add for Coord<N> {
+    type Output = Coord<N>;
+    fn add(mut self, other: Coord<N>) -> Coord<N> {
+        self += other;
+        self
+    }
+}
+
+impl<const N: usize> AddAssign for Coord<N> {
+    fn add_assign(&mut self, other: Coord<N>) {
+        (0..N).for_each(|axis| self[axis] += other[axis]);
+    }
+}
+
+impl<const N: usize> Sub for Coord<N> {
+    type Output = Coord<N>;
+    fn sub(mut self, other: Coord<N>) -> Coord<N> {
+        self -= other;
+        self
+    }
+}
+
+impl<const N: usize> SubAssign for Coord<N> {
+    fn sub_assign(&mut self, other: Coord<N>) {
+        (0..N).for_each(|axis| self[axis] -= other[axis]);
+    }
+}
+
+impl<const N: usize> Neg for Coord<N> {
+    type Output = Coord<N>;
+    fn neg(self) -> Coord<N> {
+        Coord(self.0.map(|value| -value))
+    }
+}
+
+impl<const N: usize> Mul<i32> for Coord<N> {
+    type Output = Coord<N>;
+    fn mul(self, scalar: i32) -> Coord<N> {
+        Coord(self.0.map(|value| value * scalar))
+    }
+}
+
+impl<const N: usize> std::fmt::Display for Coord<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let values: Vec<String> = self.0.iter().map(|value| value.to_string()).collect();
+        write!(f, "Coord({})", values.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let left = Coord::new([1, 2, 3]);
+        let right = Coord::new([3, -2, 1]);
+        assert_eq!(Coord::new([4, 0, 4]), left + right);
+        assert_eq!(Coord::new([-2, 4, 2]), left - right);
+        assert_eq!(Coord::new([-1, -2, -3]), -left);
+        assert_eq!(Coord::new([2, 4, 6]), left * 2);
+        assert_eq!(Coord::new([1, -1, 1]), right.signum());
+        assert_eq!(Coord::new([3, 2, 1]), right.abs());
+        assert_eq!(Coord::<3>::origin(), Coord::default());
+    }
+
+    #[test]
+    fn test_distances() {
+        let left = Coord::new([1, 1]);
+        let right = Coord::new([4, -1]);
+        assert_eq!(5, left.manhattan(&right));
+        assert_eq!(3, left.chebyshev(&right));
+        assert_eq!(0, left.chebyshev(&left));
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let origin = Coord::new([5, 5]);
+        let orthogonal: Vec<_> = origin.orthogonal_neighbors().collect();
+        assert_eq!(
+            vec![
+                Coord::new([6, 5]),
+                Coord::new([4, 5]),
+                Coord::new([5, 6]),
+                Coord::new([5, 4])
+            ],
+            orthogonal
+        );
+        assert_eq!(8, origin.neighbors().count());
+        assert!(origin.neighbors().all(|n| n.chebyshev(&origin) == 1));
+
+        assert_eq!(6, Coord3::origin().orthogonal_neighbors().count());
+        assert_eq!(26, Coord3::origin().neighbors().count());
+        assert_eq!(80, Coord::<4>::origin().neighbors().count());
+    }
+
+    #[test]
+    fn test_tuple_conversion() {
+        assert_eq!((1, -2), Coord2::new([1, -2]).into());
+        assert_eq!((1, -2, 3), Coord3::new([1, -2, 3]).into());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!("Coord(1,-2)", Coord::new([1, -2]).to_string());
+    }
+}
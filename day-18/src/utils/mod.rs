@@ -0,0 +1,2 @@
+pub mod coord;
+pub mod rect;
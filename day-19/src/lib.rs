@@ -1,110 +1,155 @@
+pub mod utils;
+use rayon::prelude::*;
 use regex::Regex;
-
-#[derive(Debug, Copy, Clone, Hash, Default, Eq, PartialEq)]
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use utils::search::{dfs, SearchState};
+
+// run states are copied for every branch of the search, so they hold fixed
+// size arrays rather than vecs. blueprints can't have more than this many
+// resources or recipes.
+const MAX_RESOURCES: usize = 8;
+
+// a blueprint is a list of robot recipes, each robot collects one unit per
+// minute of the resource it's named after. resources are referred to by their
+// index in `resources`, in the order they were first mentioned.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
 struct Blueprint {
     id: usize,
-    ore_cost: Cost,
-    clay_cost: Cost,
-    obsidian_cost: Cost,
-    geode_cost: Cost,
-    max_ore_bots: usize,
-    max_clay_bots: usize,
-    max_obsidian_bots: usize,
+    resources: Vec<String>,
+    recipes: Vec<Recipe>,
+    // the resource we're trying to collect as much of as possible
+    target: usize,
+    // we can only spend one recipe's worth of resources each minute, so there's
+    // no point having more bots collecting a resource than the most any recipe
+    // costs. unlimited for the target as we never spend it.
+    max_bots: Vec<u32>,
 }
 impl Blueprint {
     fn from_input(input: &str) -> Vec<Blueprint> {
-        let regex = Regex::new(r"\d+").unwrap();
+        let id_regex = Regex::new(r"Blueprint (\d+):").unwrap();
+        let recipe_regex = Regex::new(r"Each (\w+) robot costs ([^.]+)\.").unwrap();
+        let cost_regex = Regex::new(r"(\d+) (\w+)").unwrap();
 
         input
             .lines()
+            .filter(|line| !line.trim().is_empty())
             .map(|line| {
-                let numbers: Vec<usize> = regex
+                let id = match id_regex.captures(line) {
+                    Some(captures) => captures[1].parse().unwrap(),
+                    None => panic!("no blueprint id in {}", line),
+                };
+
+                let mut resources = vec![];
+                let recipes: Vec<Recipe> = recipe_regex
                     .captures_iter(line)
-                    .map(|v| v[0].parse().unwrap())
+                    .map(|recipe| Recipe {
+                        produces: intern(&mut resources, &recipe[1]),
+                        cost: cost_regex
+                            .captures_iter(&recipe[2])
+                            .map(|cost| {
+                                (intern(&mut resources, &cost[2]), cost[1].parse().unwrap())
+                            })
+                            .collect(),
+                    })
+                    .collect();
+
+                if recipes.is_empty() {
+                    panic!("no robot recipes in {}", line);
+                }
+                if resources.len() > MAX_RESOURCES || recipes.len() > MAX_RESOURCES {
+                    panic!(
+                        "more than {} resources or recipes in {}",
+                        MAX_RESOURCES, line
+                    );
+                }
+
+                // geodes if we have them, otherwise whatever the last robot makes
+                let target = resources
+                    .iter()
+                    .position(|resource| resource == "geode")
+                    .unwrap_or(recipes[recipes.len() - 1].produces);
+
+                let max_bots = (0..resources.len())
+                    .map(|resource| match resource == target {
+                        true => u32::MAX,
+                        false => recipes
+                            .iter()
+                            .flat_map(|recipe| recipe.cost.iter())
+                            .filter(|(spent, _)| *spent == resource)
+                            .map(|(_, amount)| *amount)
+                            .max()
+                            .unwrap_or(0),
+                    })
                     .collect();
+
                 Blueprint {
-                    id: numbers[0],
-                    ore_cost: Cost {
-                        ore: numbers[1],
-                        clay: 0,
-                        obsidian: 0,
-                    },
-                    clay_cost: Cost {
-                        ore: numbers[2],
-                        clay: 0,
-                        obsidian: 0,
-                    },
-                    obsidian_cost: Cost {
-                        ore: numbers[3],
-                        clay: numbers[4],
-                        obsidian: 0,
-                    },
-                    geode_cost: Cost {
-                        ore: numbers[5],
-                        clay: 0,
-                        obsidian: numbers[6],
-                    },
-                    max_ore_bots: numbers[1].max(numbers[2]).max(numbers[3]).max(numbers[5]),
-                    max_clay_bots: numbers[4],
-                    max_obsidian_bots: numbers[6],
+                    id,
+                    resources,
+                    recipes,
+                    target,
+                    max_bots,
                 }
             })
             .collect()
     }
 }
 
-#[derive(Debug, Clone, Copy, Default, Hash, Eq, PartialEq)]
-struct Cost {
-    ore: usize,
-    clay: usize,
-    obsidian: usize,
+fn intern(resources: &mut Vec<String>, name: &str) -> usize {
+    match resources.iter().position(|resource| resource == name) {
+        Some(index) => index,
+        None => {
+            resources.push(name.to_string());
+            resources.len() - 1
+        }
+    }
 }
 
-#[derive(Debug, Default, Copy, Clone, Hash, Eq, PartialEq)]
-struct RunState {
-    blueprint: Blueprint,
+// a robot which collects the `produces` resource, costing (resource, amount)
+// for every resource it needs.
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq)]
+struct Recipe {
+    produces: usize,
+    cost: Vec<(usize, u32)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RunState<'a> {
+    blueprint: &'a Blueprint,
     minute: u32,
     max_minutes: u32,
-    ore_inventory: usize,
-    clay_inventory: usize,
-    obsidian_inventory: usize,
-    geode_inventory: u32,
-    ore_bots: usize,
-    clay_bots: usize,
-    obsidian_bots: usize,
-    geode_bots: u32,
+    // both indexed by resource, anything past the blueprint's resources is 0
+    inventory: [u32; MAX_RESOURCES],
+    bots: [u32; MAX_RESOURCES],
 }
-impl RunState {
-    fn new(blueprint: &Blueprint, max_minutes: u32) -> RunState {
+impl<'a> RunState<'a> {
+    // we start with a single bot from the first recipe
+    fn new(blueprint: &'a Blueprint, max_minutes: u32) -> RunState<'a> {
+        let mut bots = [0; MAX_RESOURCES];
+        bots[blueprint.recipes[0].produces] = 1;
         RunState {
+            blueprint,
+            minute: 0,
             max_minutes,
-            ore_bots: 1,
-            blueprint: *blueprint,
-            ..Default::default()
+            inventory: [0; MAX_RESOURCES],
+            bots,
         }
     }
 
     // executes the current run state for the specified decision. if this
     // decision is not valid it will panic. returns the various decisions that
-    // could be made at a choice intersection. returns none if we are complete.
-    fn run(&mut self, mut decision: Decision, current_best: u32) -> Option<Vec<Decision>> {
-        let ore_cost = self.blueprint.ore_cost;
-        let clay_cost = self.blueprint.clay_cost;
-        let obsidian_cost = self.blueprint.obsidian_cost;
-        let geode_cost = self.blueprint.geode_cost;
-
-        let mut skipped_ore_build = false;
-        let mut skipped_clay_build = false;
-        let mut skipped_obsidian_build = false;
-        let mut skipped_geode_build = false;
+    // could be made at a choice intersection. returns none if we are complete,
+    // or as soon as we can't beat the current best.
+    fn run(&mut self, mut decision: Decision, current_best: Option<u32>) -> Option<Vec<Decision>> {
+        let blueprint = self.blueprint;
 
         // if we made the decision to idle, then consider what we're not doing -
         // as it won't make sense to do it as our next decision.
+        let mut skipped = [false; MAX_RESOURCES];
         if decision == Decision::Idle {
-            skipped_ore_build = self.can_afford(&ore_cost);
-            skipped_clay_build = self.can_afford(&clay_cost);
-            skipped_obsidian_build = self.can_afford(&obsidian_cost);
-            skipped_geode_build = self.can_afford(&geode_cost);
+            for (robot, skip) in skipped.iter_mut().enumerate().take(blueprint.recipes.len()) {
+                *skip = self.can_afford(Decision::Build(robot));
+            }
         }
 
         loop {
@@ -113,43 +158,13 @@ impl RunState {
                 break;
             }
 
-            // if, even if we built a bot geode bot every round, we couldn't
+            // if, even if we built a geode bot every round, we couldn't
             // beat our max then short circuit.
-            let turns_remaining = self.max_minutes - self.minute;
-            let geode_max_growth = ((turns_remaining - 1) * turns_remaining) / 2;
-            let max_potential =
-                (self.geode_bots * turns_remaining) + geode_max_growth + self.geode_inventory;
-            if max_potential <= current_best {
+            if current_best.is_some_and(|best| self.max_potential_geodes() <= best) {
                 break;
             }
 
-            self.minute += 1;
-
-            match decision {
-                Decision::BuildOreBot => {
-                    self.apply_cost(&ore_cost);
-                    self.increment_inventory();
-                    self.ore_bots += 1;
-                }
-                Decision::BuildClayBot => {
-                    self.apply_cost(&clay_cost);
-                    self.increment_inventory();
-                    self.clay_bots += 1;
-                }
-                Decision::BuildObsidianBot => {
-                    self.apply_cost(&obsidian_cost);
-                    self.increment_inventory();
-                    self.obsidian_bots += 1;
-                }
-                Decision::BuildGeodeBot => {
-                    self.apply_cost(&geode_cost);
-                    self.increment_inventory();
-                    self.geode_bots += 1;
-                }
-                Decision::Idle => {
-                    self.increment_inventory();
-                }
-            }
+            self.execute(decision);
 
             // calculates what we can/should build. we should only consider
             // building something if we can both afford it, and we don't already
@@ -158,121 +173,325 @@ impl RunState {
             // possible. it does not make sense to be able to build something,
             // idle for a few turns, then build it later, thus don't consider
             // building something we've previously skipped.
-            let build_ore_bot = !skipped_ore_build
-                && self.ore_bots < self.blueprint.max_ore_bots
-                && self.can_afford(&ore_cost);
-
-            let build_clay_bot = !skipped_clay_build
-                && self.clay_bots < self.blueprint.max_clay_bots
-                && self.can_afford(&clay_cost);
-
-            let build_obsidian_bot = !skipped_obsidian_build
-                && self.obsidian_bots < self.blueprint.max_obsidian_bots
-                && self.can_afford(&obsidian_cost);
-
-            let build_geode_bot = !skipped_geode_build && self.can_afford(&geode_cost);
-
-            match (
-                build_ore_bot,
-                build_clay_bot,
-                build_obsidian_bot,
-                build_geode_bot,
-            ) {
-                // can't build anything, continue the loop
-                (false, false, false, false) => {
-                    decision = Decision::Idle;
+            let mut decisions = vec![];
+            for (robot, recipe) in blueprint.recipes.iter().enumerate() {
+                if !skipped[robot]
+                    && self.bots[recipe.produces] < blueprint.max_bots[recipe.produces]
+                    && self.can_afford(Decision::Build(robot))
+                {
+                    decisions.push(Decision::Build(robot));
                 }
-                // else we have a decision to make, build out and return choices
-                _ => {
-                    let mut decisions = vec![];
-                    if build_ore_bot {
-                        decisions.push(Decision::BuildOreBot);
-                    }
-                    if build_clay_bot {
-                        decisions.push(Decision::BuildClayBot);
-                    }
-                    if build_obsidian_bot {
-                        decisions.push(Decision::BuildObsidianBot);
-                    }
-                    if build_geode_bot {
-                        decisions.push(Decision::BuildGeodeBot);
-                    }
-                    decisions.push(Decision::Idle);
+            }
 
-                    return Some(decisions);
-                }
+            // can't build anything, continue the loop. else we have a
+            // decision to make, return the choices.
+            if decisions.is_empty() {
+                decision = Decision::Idle;
+            } else {
+                decisions.push(Decision::Idle);
+                return Some(decisions);
             }
         }
 
         None
     }
 
-    fn increment_inventory(&mut self) {
-        self.ore_inventory += self.ore_bots;
-        self.clay_inventory += self.clay_bots;
-        self.obsidian_inventory += self.obsidian_bots;
-        self.geode_inventory += self.geode_bots;
+    // spends a single minute carrying out the decision, paying for any bot
+    // up front and only adding it once the existing bots have collected.
+    fn execute(&mut self, decision: Decision) {
+        self.minute += 1;
+        if let Decision::Build(robot) = decision {
+            for (resource, amount) in &self.blueprint.recipes[robot].cost {
+                self.inventory[*resource] -= amount;
+            }
+        }
+
+        for resource in 0..self.blueprint.resources.len() {
+            self.inventory[resource] += self.bots[resource];
+        }
+
+        if let Decision::Build(robot) = decision {
+            self.bots[self.blueprint.recipes[robot].produces] += 1;
+        }
+    }
+
+    fn can_afford(&self, decision: Decision) -> bool {
+        match decision {
+            Decision::Idle => true,
+            Decision::Build(robot) => {
+                for (resource, amount) in &self.blueprint.recipes[robot].cost {
+                    if self.inventory[*resource] < *amount {
+                        return false;
+                    }
+                }
+                true
+            }
+        }
     }
 
-    fn apply_cost(&mut self, cost: &Cost) {
-        self.ore_inventory -= cost.ore;
-        self.clay_inventory -= cost.clay;
-        self.obsidian_inventory -= cost.obsidian;
+    fn inventory(&self) -> Inventory {
+        let named = |amounts: &[u32]| {
+            self.blueprint
+                .resources
+                .iter()
+                .cloned()
+                .zip(amounts.iter().copied())
+                .collect()
+        };
+        let resources = self.blueprint.resources.len();
+        Inventory {
+            resources: named(&self.inventory[..resources]),
+            robots: named(&self.bots[..resources]),
+        }
     }
 
-    fn can_afford(&self, cost: &Cost) -> bool {
-        self.ore_inventory >= cost.ore
-            && self.clay_inventory >= cost.clay
-            && self.obsidian_inventory >= cost.obsidian
+    // target resource we'll end up with if we never build another bot
+    fn guaranteed_geodes(&self) -> u32 {
+        let target = self.blueprint.target;
+        self.inventory[target] + (self.bots[target] * (self.max_minutes - self.minute))
+    }
+
+    // target resource we'd end up with if we could build a target bot every
+    // remaining minute, nothing from this state can do better.
+    fn max_potential_geodes(&self) -> u32 {
+        let turns_remaining = self.max_minutes - self.minute;
+        let geode_max_growth = (turns_remaining.saturating_sub(1) * turns_remaining) / 2;
+        self.guaranteed_geodes() + geode_max_growth
     }
 }
 
 // as we iterate through the run state, at various points we can make the
-// following decisions when we have enough resources to build something.
+// following decisions when we have enough resources to build something. builds
+// refer to the robot by its index in the blueprint's recipes.
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 enum Decision {
     Idle,
-    BuildOreBot,
-    BuildClayBot,
-    BuildObsidianBot,
-    BuildGeodeBot,
+    Build(usize),
+}
+
+// a robot being built, the minute is the one spent building it so the bot
+// starts collecting from the following minute.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Build {
+    pub minute: u32,
+    pub robot: String,
+}
+
+// the best amount of the target resource (geodes for the puzzle) for a
+// blueprint and the builds that get us there, any minute without a build is
+// spent idling.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BuildOrder {
+    pub geodes: u32,
+    pub builds: Vec<Build>,
+}
+
+// what we're left with after a run, keyed by resource name
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Inventory {
+    pub resources: BTreeMap<String, u32>,
+    pub robots: BTreeMap<String, u32>,
+}
+
+// builds made so far, newest first. shared between branches so that cloning a
+// branch doesn't copy the whole history.
+#[derive(Debug)]
+struct History {
+    minute: u32,
+    robot: usize,
+    previous: Option<Rc<History>>,
+}
+
+// node in our search tree, the run state along with the decision we're about
+// to make from it. no decision means the run state has used all its minutes.
+#[derive(Debug, Clone)]
+struct Branch<'a>(RunState<'a>, Option<Decision>, Option<Rc<History>>);
+impl Branch<'_> {
+    // runs the decision through to the next choice, giving up early on
+    // anything that can't beat the best if we know it.
+    fn advance(&self, best: Option<u32>) -> Vec<Self> {
+        let Branch(mut run_state, Some(decision), ref history) = *self else {
+            return vec![];
+        };
+
+        let history = match decision {
+            Decision::Idle => history.clone(),
+            _ if run_state.minute == run_state.max_minutes => history.clone(),
+            Decision::Build(robot) => Some(Rc::new(History {
+                minute: run_state.minute + 1,
+                robot,
+                previous: history.clone(),
+            })),
+        };
+
+        match run_state.run(decision, best) {
+            Some(decisions) => decisions
+                .into_iter()
+                .map(|next_decision| Branch(run_state, Some(next_decision), history.clone()))
+                .collect(),
+            None => vec![Branch(run_state, None, history)],
+        }
+    }
+
+    fn builds(&self) -> Vec<Build> {
+        let blueprint = self.0.blueprint;
+        let mut builds = vec![];
+        let mut current = self.2.as_ref();
+        while let Some(history) = current {
+            let produces = blueprint.recipes[history.robot].produces;
+            builds.push(Build {
+                minute: history.minute,
+                robot: blueprint.resources[produces].clone(),
+            });
+            current = history.previous.as_ref();
+        }
+        builds.reverse();
+        builds
+    }
+}
+impl SearchState for Branch<'_> {
+    type Score = u32;
+
+    fn successors(&self) -> Vec<Self> {
+        self.advance(None)
+    }
+
+    fn successors_beating(&self, best: u32) -> Vec<Self> {
+        self.advance(Some(best))
+    }
+
+    fn score(&self) -> u32 {
+        self.0.guaranteed_geodes()
+    }
+
+    // if, even if we built a geode bot every round, we couldn't beat our
+    // max then there's no point exploring further.
+    fn bound(&self) -> Option<u32> {
+        Some(self.0.max_potential_geodes())
+    }
 }
 
 pub fn part_one(input: &str) -> u32 {
-    Blueprint::from_input(input)
+    evaluate_blueprints(input, 24, usize::MAX, 0)
         .iter()
-        .map(|blueprint| calculate_max_geodes(blueprint, 24) * blueprint.id as u32)
+        .map(|(id, geodes)| geodes * *id as u32)
         .sum()
 }
 
 pub fn part_two(input: &str) -> u32 {
-    Blueprint::from_input(input)
+    evaluate_blueprints(input, 32, 3, 0)
         .iter()
-        .take(3)
-        .map(|blueprint| calculate_max_geodes(blueprint, 32))
+        .map(|(_, geodes)| geodes)
         .product()
 }
 
-fn calculate_max_geodes(blueprint: &Blueprint, num_minutes: u32) -> u32 {
-    // effectively maps our decision trees at the various run states that we
-    // need track, will pop/push into this as we come across different decisions
-    // that we need to make.
-    let mut run_states = vec![(RunState::new(blueprint, num_minutes), Decision::Idle)];
-    let mut max_geodes = 0;
-
-    while let Some((mut run_state, decision)) = run_states.pop() {
-        match run_state.run(decision, max_geodes) {
-            Some(decisions) => {
-                for next_decision in decisions {
-                    run_states.push((run_state, next_decision));
+// calculates the max geodes for (up to) the first `take` blueprints, each one
+// is independent so they're spread over a pool of `threads` threads - 0 lets
+// rayon pick based on the number of cpus. returns (blueprint id, max geodes)
+// in the same order as the input regardless of which finishes first.
+pub fn evaluate_blueprints(
+    input: &str,
+    num_minutes: u32,
+    take: usize,
+    threads: usize,
+) -> Vec<(usize, u32)> {
+    let blueprints: Vec<_> = Blueprint::from_input(input)
+        .into_iter()
+        .take(take)
+        .collect();
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .expect("could not build thread pool");
+
+    pool.install(|| {
+        blueprints
+            .par_iter()
+            .map(|blueprint| (blueprint.id, calculate_max_geodes(blueprint, num_minutes)))
+            .collect()
+    })
+}
+
+// the optimal build order for the blueprint with the given id
+pub fn best_build_order(input: &str, blueprint_id: usize, num_minutes: u32) -> BuildOrder {
+    match find_blueprint(input, blueprint_id) {
+        Ok(blueprint) => calculate_build_order(&blueprint, num_minutes),
+        Err(error) => panic!("{}", error),
+    }
+}
+
+// replays the builds against the blueprint minute by minute, returning what
+// we're left with at the end, or why the build order isn't possible.
+pub fn verify_build_order(
+    input: &str,
+    blueprint_id: usize,
+    num_minutes: u32,
+    builds: &[Build],
+) -> Result<Inventory, String> {
+    let blueprint = find_blueprint(input, blueprint_id)?;
+    let mut run_state = RunState::new(&blueprint, num_minutes);
+    let mut builds = builds.iter().peekable();
+
+    while run_state.minute < num_minutes {
+        let minute = run_state.minute + 1;
+        let decision = match builds.next_if(|build| build.minute == minute) {
+            Some(build) => {
+                let Some(robot) = find_recipe(&blueprint, &build.robot) else {
+                    return Err(format!("no recipe for a {} robot", build.robot));
+                };
+                if !run_state.can_afford(Decision::Build(robot)) {
+                    return Err(format!(
+                        "can't afford {} robot at minute {}",
+                        build.robot, minute
+                    ));
                 }
+                Decision::Build(robot)
             }
-            None => {
-                max_geodes = max_geodes.max(run_state.geode_inventory);
-            }
-        }
+            None => Decision::Idle,
+        };
+
+        run_state.execute(decision);
+    }
+
+    match builds.next() {
+        Some(build) => Err(format!(
+            "{} robot at minute {} is out of order or out of time",
+            build.robot, build.minute
+        )),
+        None => Ok(run_state.inventory()),
+    }
+}
+
+fn find_blueprint(input: &str, blueprint_id: usize) -> Result<Blueprint, String> {
+    Blueprint::from_input(input)
+        .into_iter()
+        .find(|blueprint| blueprint.id == blueprint_id)
+        .ok_or_else(|| format!("no blueprint with id {}", blueprint_id))
+}
+
+// index of the recipe for the named robot
+fn find_recipe(blueprint: &Blueprint, robot: &str) -> Option<usize> {
+    blueprint
+        .recipes
+        .iter()
+        .position(|recipe| blueprint.resources[recipe.produces] == robot)
+}
+
+fn calculate_max_geodes(blueprint: &Blueprint, num_minutes: u32) -> u32 {
+    calculate_build_order(blueprint, num_minutes).geodes
+}
+
+fn calculate_build_order(blueprint: &Blueprint, num_minutes: u32) -> BuildOrder {
+    let start = Branch(
+        RunState::new(blueprint, num_minutes),
+        Some(Decision::Idle),
+        None,
+    );
+    let best = dfs(start);
+    BuildOrder {
+        geodes: best.score(),
+        builds: best.builds(),
     }
-    max_geodes
 }
 
 #[cfg(test)]
@@ -292,6 +511,132 @@ mod tests {
         assert_eq!(1349, part_one(INPUT));
     }
 
+    #[test]
+    fn test_evaluate_blueprints_ordering() {
+        let expected = vec![(1, 9), (2, 12)];
+        assert_eq!(expected, evaluate_blueprints(EXAMPLE_INPUT, 24, 2, 1));
+        assert_eq!(expected, evaluate_blueprints(EXAMPLE_INPUT, 24, 2, 4));
+        assert_eq!(1, evaluate_blueprints(EXAMPLE_INPUT, 24, 1, 2).len());
+    }
+
+    #[test]
+    fn test_build_order() {
+        let order = best_build_order(EXAMPLE_INPUT, 1, 24);
+        assert_eq!(9, order.geodes);
+
+        let inventory = verify_build_order(EXAMPLE_INPUT, 1, 24, &order.builds).unwrap();
+        assert_eq!(9, inventory.resources["geode"]);
+        assert_eq!(
+            order.builds.len() as u32,
+            inventory.robots.values().sum::<u32>() - 1
+        );
+    }
+
+    #[test]
+    fn test_verify_build_order() {
+        // the build order from the puzzle description
+        let builds: Vec<_> = [
+            (3, "clay"),
+            (5, "clay"),
+            (7, "clay"),
+            (11, "obsidian"),
+            (12, "clay"),
+            (15, "obsidian"),
+            (18, "geode"),
+            (21, "geode"),
+        ]
+        .map(|(minute, robot)| Build {
+            minute,
+            robot: robot.to_string(),
+        })
+        .into();
+
+        let inventory = verify_build_order(EXAMPLE_INPUT, 1, 24, &builds).unwrap();
+        let counts = |map: &BTreeMap<String, u32>| -> Vec<u32> {
+            ["ore", "clay", "obsidian", "geode"]
+                .iter()
+                .map(|name| map[*name])
+                .collect()
+        };
+        assert_eq!(vec![6, 41, 8, 9], counts(&inventory.resources));
+        assert_eq!(vec![1, 4, 2, 2], counts(&inventory.robots));
+
+        let too_early = [Build {
+            minute: 2,
+            robot: "clay".to_string(),
+        }];
+        assert_eq!(
+            Err("can't afford clay robot at minute 2".to_string()),
+            verify_build_order(EXAMPLE_INPUT, 1, 24, &too_early)
+        );
+
+        let too_late = [Build {
+            minute: 25,
+            robot: "ore".to_string(),
+        }];
+        assert!(verify_build_order(EXAMPLE_INPUT, 1, 24, &too_late).is_err());
+
+        let unknown = [Build {
+            minute: 3,
+            robot: "diamond".to_string(),
+        }];
+        assert_eq!(
+            Err("no recipe for a diamond robot".to_string()),
+            verify_build_order(EXAMPLE_INPUT, 1, 24, &unknown)
+        );
+    }
+
+    #[test]
+    fn test_verify_unknown_blueprint() {
+        let builds = [Build {
+            minute: 3,
+            robot: "clay".to_string(),
+        }];
+        assert_eq!(
+            Err("no blueprint with id 3".to_string()),
+            verify_build_order(EXAMPLE_INPUT, 3, 24, &builds)
+        );
+    }
+
+    #[test]
+    fn test_parse_recipes() {
+        let blueprint = &Blueprint::from_input(EXAMPLE_INPUT)[0];
+        assert_eq!(1, blueprint.id);
+        assert_eq!(
+            vec!["ore", "clay", "obsidian", "geode"],
+            blueprint.resources
+        );
+        assert_eq!(3, blueprint.target);
+        assert_eq!(vec![4, 14, 7, u32::MAX], blueprint.max_bots);
+        assert_eq!(vec![(0, 3), (1, 14)], blueprint.recipes[2].cost);
+    }
+
+    #[test]
+    fn test_custom_recipes() {
+        // no geodes here, so we're after whatever the last robot collects
+        let input = "Blueprint 7: Each wood robot costs 1 wood. Each gem robot costs 2 wood.";
+        let blueprint = &Blueprint::from_input(input)[0];
+        assert_eq!(1, blueprint.target);
+        assert_eq!(vec![2, u32::MAX], blueprint.max_bots);
+
+        // idle, then a gem robot in minute 3 which collects in minute 4
+        let order = best_build_order(input, 7, 4);
+        assert_eq!(1, order.geodes);
+        assert_eq!(
+            vec![Build {
+                minute: 3,
+                robot: "gem".to_string()
+            }],
+            order.builds
+        );
+        assert_eq!(vec![(7, 2)], evaluate_blueprints(input, 5, 1, 1));
+
+        // costs in resources nobody collects can never be afforded
+        let input =
+            "Blueprint 1: Each ore robot costs 1 ore. Each geode robot costs 1 ore and 1 dust.";
+        assert_eq!(vec![(1, 0)], evaluate_blueprints(input, 10, 1, 1));
+    }
+
     #[test]
     fn test_part_two_example() {
         assert_eq!(3472, part_two(EXAMPLE_INPUT));
@@ -1,76 +1,127 @@
-#[derive(Debug)]
-struct Entry {
-    value: i64,
-    order: usize,
-}
-impl Entry {
-    fn new(value: &str, order: usize, decryption_key: i64) -> Entry {
-        Entry {
-            value: value.parse::<i64>().unwrap() * decryption_key,
-            order,
-        }
-    }
-}
+pub mod utils;
+use utils::indexed_list::IndexedList;
 
+// the numbers in their original order, along with where each one currently
+// sits in the mixed sequence. a number is referred to by its original index so
+// duplicate values are never confused with one another.
 #[derive(Debug)]
-struct Entries(Vec<Entry>);
+struct Entries {
+    values: Vec<i64>,
+    order: IndexedList,
+}
 impl Entries {
     fn new(input: &str, decryption_key: i64) -> Entries {
-        Entries(
-            input
-                .lines()
-                .enumerate()
-                .map(|(order, line)| Entry::new(line, order, decryption_key))
-                .collect(),
-        )
+        let values: Vec<i64> = input
+            .lines()
+            .map(|line| line.parse::<i64>().unwrap() * decryption_key)
+            .collect();
+        let order = IndexedList::new(values.len());
+        Entries { values, order }
     }
 
-    fn at_order_index(&self, idx: usize) -> Option<(&Entry, usize)> {
-        self.0
-            .iter()
-            .position(|e| e.order == idx)
-            .map(|e| (self.0.get(e).unwrap(), e))
+    // moves every number once, in their original order
+    fn mix(&mut self) {
+        if self.values.len() < 2 {
+            return;
+        }
+
+        let modified_len = self.values.len() as i64 - 1; // offset by 1 for the removal
+        for (original, value) in self.values.iter().enumerate() {
+            let move_from = self.order.position(original);
+            let move_to = (move_from as i64 + value).rem_euclid(modified_len) as usize;
+            self.order.move_to(original, move_to);
+        }
     }
 
-    fn mix(&mut self, times: usize) {
-        for _ in 0..times {
-            let mut current_order_idx = 0;
+    fn value_at(&self, idx: usize) -> i64 {
+        self.values[self.order.get(idx % self.values.len()).unwrap()]
+    }
 
-            while let Some((current, move_from)) = self.at_order_index(current_order_idx) {
-                let relative_move = move_from as i64 + current.value;
-                let modified_len = self.0.len() as i64 - 1; // offset by 1 for future removal
-                let move_to = (relative_move.rem_euclid(modified_len)) as usize;
+    fn anchor_position(&self, anchor: i64) -> usize {
+        match self.values.iter().position(|value| *value == anchor) {
+            Some(original) => self.order.position(original),
+            None => panic!("no {} in the input", anchor),
+        }
+    }
 
-                let removed = self.0.remove(move_from);
-                self.0.insert(move_to, removed);
+    // the values in their current mixed order, starting from the anchor
+    fn sequence(&self, anchor: i64) -> Vec<i64> {
+        let start = self.anchor_position(anchor);
+        (start..start + self.values.len())
+            .map(|idx| self.value_at(idx))
+            .collect()
+    }
 
-                current_order_idx += 1;
-            }
-        }
+    fn grove_coordinates(&self, config: &MixConfig) -> i64 {
+        let anchor_position = self.anchor_position(config.anchor * config.decryption_key);
+        config
+            .offsets
+            .iter()
+            .map(|offset| self.value_at(anchor_position + offset))
+            .sum()
     }
+}
 
-    fn value_at(&self, idx: usize) -> i64 {
-        self.0.get(idx % self.0.len()).unwrap().value
+// everything which can be tweaked about decrypting the file. every number is
+// multiplied by the decryption key then mixed `rounds` times, the answer is the
+// sum of the numbers `offsets` after the anchor (wrapping around). the anchor
+// is given before it's multiplied by the key.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MixConfig {
+    pub decryption_key: i64,
+    pub rounds: usize,
+    pub offsets: Vec<usize>,
+    pub anchor: i64,
+}
+impl MixConfig {
+    pub fn part_one() -> MixConfig {
+        MixConfig {
+            decryption_key: 1,
+            rounds: 1,
+            offsets: vec![1000, 2000, 3000],
+            anchor: 0,
+        }
     }
 
-    fn grove_coordinates(&self) -> i64 {
-        let zero_position = self.0.iter().position(|entry| entry.value == 0).unwrap();
-        self.value_at(zero_position + 1000)
-            + self.value_at(zero_position + 2000)
-            + self.value_at(zero_position + 3000)
+    pub fn part_two() -> MixConfig {
+        MixConfig {
+            decryption_key: 811589153,
+            rounds: 10,
+            ..MixConfig::part_one()
+        }
+    }
+}
+impl Default for MixConfig {
+    fn default() -> Self {
+        MixConfig::part_one()
     }
 }
 
 pub fn part_one(input: &str) -> i64 {
-    let mut entries = Entries::new(input, 1);
-    entries.mix(1);
-    entries.grove_coordinates()
+    decrypt(input, &MixConfig::part_one())
 }
 
 pub fn part_two(input: &str) -> i64 {
-    let mut entries = Entries::new(input, 811589153);
-    entries.mix(10);
-    entries.grove_coordinates()
+    decrypt(input, &MixConfig::part_two())
+}
+
+pub fn decrypt(input: &str, config: &MixConfig) -> i64 {
+    let mut entries = Entries::new(input, config.decryption_key);
+    (0..config.rounds).for_each(|_| entries.mix());
+    entries.grove_coordinates(config)
+}
+
+// the mixed sequence after each round, starting from the anchor so rounds are
+// easy to compare - the sequence is circular so where it starts is arbitrary.
+pub fn mix_rounds(input: &str, config: &MixConfig) -> Vec<Vec<i64>> {
+    let mut entries = Entries::new(input, config.decryption_key);
+    let anchor = config.anchor * config.decryption_key;
+    (0..config.rounds)
+        .map(|_| {
+            entries.mix();
+            entries.sequence(anchor)
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -80,6 +131,126 @@ mod tests {
     static EXAMPLE_INPUT: &str = include_str!("../input.example.txt");
     static INPUT: &str = include_str!("../input.txt");
 
+    // the original quadratic mix, finds each number with a linear search then
+    // removes and re-inserts it in a plain vec.
+    fn reference_mix(values: &[i64], times: usize) -> Vec<(usize, i64)> {
+        let mut entries: Vec<(usize, i64)> = values.iter().copied().enumerate().collect();
+        for _ in 0..times {
+            for order in 0..entries.len() {
+                let move_from = entries.iter().position(|e| e.0 == order).unwrap();
+                let relative_move = move_from as i64 + entries[move_from].1;
+                let modified_len = entries.len() as i64 - 1;
+                let move_to = (relative_move.rem_euclid(modified_len)) as usize;
+
+                let removed = entries.remove(move_from);
+                entries.insert(move_to, removed);
+            }
+        }
+        entries
+    }
+
+    fn assert_matches_reference(input: &str, decryption_key: i64, times: usize) {
+        let mut entries = Entries::new(input, decryption_key);
+        let mut expected = reference_mix(&entries.values, times);
+        (0..times).for_each(|_| entries.mix());
+
+        // both start from the first zero in the input
+        let zero = entries.values.iter().position(|v| *v == 0).unwrap();
+        let start = expected.iter().position(|e| e.0 == zero).unwrap();
+        expected.rotate_left(start);
+        let expected: Vec<i64> = expected.into_iter().map(|e| e.1).collect();
+        assert_eq!(expected, entries.sequence(0));
+    }
+
+    // numbers from a simple lcg, with plenty of duplicates
+    fn generated_input(count: usize, range: i64) -> String {
+        let mut seed = 17_u64;
+        let mut numbers: Vec<String> = (0..count)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                ((seed >> 33) as i64 % (range * 2 + 1) - range).to_string()
+            })
+            .collect();
+        numbers[count / 2] = "0".to_string();
+        numbers.join("\n")
+    }
+
+    #[test]
+    fn test_mix_matches_reference() {
+        assert_matches_reference(EXAMPLE_INPUT, 1, 1);
+        assert_matches_reference(EXAMPLE_INPUT, 811589153, 10);
+        assert_matches_reference(INPUT, 1, 1);
+        assert_matches_reference(&generated_input(1000, 50), 1, 3);
+        assert_matches_reference(&generated_input(1000, 10_000), 811589153, 2);
+    }
+
+    #[test]
+    fn test_mix_large() {
+        let input = generated_input(200_000, 10_000);
+        let mut entries = Entries::new(&input, 811589153);
+        entries.mix();
+
+        let mut mixed = entries.sequence(0);
+        let mut expected = entries.values.clone();
+        mixed.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(expected, mixed);
+    }
+
+    #[test]
+    fn test_mix_rounds() {
+        let rounds = mix_rounds(EXAMPLE_INPUT, &MixConfig::part_one());
+        assert_eq!(vec![vec![0, 3, -2, 1, 2, -3, 4]], rounds);
+
+        let rounds = mix_rounds(EXAMPLE_INPUT, &MixConfig::part_two());
+        assert_eq!(10, rounds.len());
+        assert_eq!(
+            vec![
+                0,
+                -2434767459,
+                3246356612,
+                -1623178306,
+                2434767459,
+                1623178306,
+                811589153
+            ],
+            rounds[0]
+        );
+        assert_eq!(
+            vec![
+                0,
+                -2434767459,
+                1623178306,
+                3246356612,
+                -1623178306,
+                2434767459,
+                811589153
+            ],
+            rounds[9]
+        );
+    }
+
+    #[test]
+    fn test_custom_config() {
+        assert_eq!(3, decrypt(EXAMPLE_INPUT, &MixConfig::default()));
+
+        // from 3 after one mix: 3 -2 1 2 -3 4 0, so offsets 1 and 10 are -2 and 2
+        let config = MixConfig {
+            offsets: vec![1, 10],
+            anchor: 3,
+            ..MixConfig::part_one()
+        };
+        assert_eq!(0, decrypt(EXAMPLE_INPUT, &config));
+
+        let config = MixConfig {
+            rounds: 0,
+            offsets: vec![1, 2],
+            ..MixConfig::part_two()
+        };
+        // unmixed the two after 0 are 4 then (wrapping around) 1
+        assert_eq!(5 * 811589153, decrypt(EXAMPLE_INPUT, &config));
+    }
+
     #[test]
     fn test_part_one_example() {
         assert_eq!(3, part_one(EXAMPLE_INPUT));
@@ -0,0 +1,264 @@
+use std::cmp::Ordering;
+
+// the ids 0..n in some order, able to find the position of an id, the id at a
+// position and move an id to a new position all in O(log n). backed by an
+// implicit treap - a binary tree ordered by position rather than by key, kept
+// balanced by giving every node a pseudo-random priority. each id is its own
+// node so looking one up never needs a search.
+#[derive(Clone, Debug, Default)]
+pub struct IndexedList {
+    nodes: Vec<Node>,
+    root: Option<usize>,
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    left: Option<usize>,
+    right: Option<usize>,
+    parent: Option<usize>,
+    size: usize,
+    priority: u64,
+}
+
+impl Node {
+    fn new(id: usize) -> Node {
+        Node {
+            left: None,
+            right: None,
+            parent: None,
+            size: 1,
+            priority: priority(id),
+        }
+    }
+}
+
+impl IndexedList {
+    // the ids 0..len in ascending order
+    pub fn new(len: usize) -> IndexedList {
+        let mut list = IndexedList {
+            nodes: (0..len).map(Node::new).collect(),
+            root: None,
+        };
+        for id in 0..len {
+            list.root = list.merge(list.root, Some(id));
+        }
+        list
+    }
+
+    pub fn len(&self) -> usize {
+        self.size(self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // where the id currently is, the id must be in the list
+    pub fn position(&self, id: usize) -> usize {
+        let mut position = self.size(self.nodes[id].left);
+        let mut current = id;
+        while let Some(parent) = self.nodes[current].parent {
+            if self.nodes[parent].right == Some(current) {
+                position += self.size(self.nodes[parent].left) + 1;
+            }
+            current = parent;
+        }
+        position
+    }
+
+    // the id at the position, none if it's past the end
+    pub fn get(&self, mut position: usize) -> Option<usize> {
+        let mut current = self.root;
+        while let Some(node) = current {
+            let left = self.size(self.nodes[node].left);
+            match position.cmp(&left) {
+                Ordering::Less => current = self.nodes[node].left,
+                Ordering::Equal => return Some(node),
+                Ordering::Greater => {
+                    position -= left + 1;
+                    current = self.nodes[node].right;
+                }
+            }
+        }
+        None
+    }
+
+    // takes the id out of the list, it can be put back with insert
+    pub fn remove(&mut self, id: usize) {
+        let (before, rest) = self.split(self.root, self.position(id));
+        let (_, after) = self.split(rest, 1);
+        self.root = self.merge(before, after);
+        self.nodes[id] = Node::new(id);
+    }
+
+    // puts a removed id back so that it ends up at the position, shuffling
+    // everything from there onwards along by one.
+    pub fn insert(&mut self, id: usize, position: usize) {
+        let (before, after) = self.split(self.root, position);
+        let joined = self.merge(before, Some(id));
+        self.root = self.merge(joined, after);
+    }
+
+    // moves the id so that it ends up at the position once it's been taken
+    // out of its current one.
+    pub fn move_to(&mut self, id: usize, position: usize) {
+        self.remove(id);
+        self.insert(id, position);
+    }
+
+    // the ids in list order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut stack = vec![];
+        let mut current = self.root;
+        std::iter::from_fn(move || {
+            while let Some(node) = current {
+                stack.push(node);
+                current = self.nodes[node].left;
+            }
+            let node = stack.pop()?;
+            current = self.nodes[node].right;
+            Some(node)
+        })
+    }
+
+    fn size(&self, tree: Option<usize>) -> usize {
+        tree.map_or(0, |node| self.nodes[node].size)
+    }
+
+    // recalculates the node's size from its children, and makes sure they
+    // point back at it.
+    fn update(&mut self, node: usize) {
+        let Node { left, right, .. } = self.nodes[node];
+        self.nodes[node].size = 1 + self.size(left) + self.size(right);
+        for child in [left, right].into_iter().flatten() {
+            self.nodes[child].parent = Some(node);
+        }
+    }
+
+    fn detach(&mut self, tree: Option<usize>) -> Option<usize> {
+        if let Some(node) = tree {
+            self.nodes[node].parent = None;
+        }
+        tree
+    }
+
+    // splits the tree into the first `count` ids and the rest
+    fn split(&mut self, tree: Option<usize>, count: usize) -> (Option<usize>, Option<usize>) {
+        let Some(node) = tree else {
+            return (None, None);
+        };
+
+        let left = self.size(self.nodes[node].left);
+        let (before, after) = if count <= left {
+            let (before, after) = self.split(self.nodes[node].left, count);
+            self.nodes[node].left = after;
+            (before, Some(node))
+        } else {
+            let (before, after) = self.split(self.nodes[node].right, count - left - 1);
+            self.nodes[node].right = before;
+            (Some(node), after)
+        };
+        self.update(node);
+
+        (self.detach(before), self.detach(after))
+    }
+
+    // joins two trees, with every id in the first before every id in the second
+    fn merge(&mut self, first: Option<usize>, second: Option<usize>) -> Option<usize> {
+        let (Some(left), Some(right)) = (first, second) else {
+            return self.detach(first.or(second));
+        };
+
+        let root = if self.nodes[left].priority > self.nodes[right].priority {
+            let merged = self.merge(self.nodes[left].right, second);
+            self.nodes[left].right = merged;
+            left
+        } else {
+            let merged = self.merge(first, self.nodes[right].left);
+            self.nodes[right].left = merged;
+            right
+        };
+        self.update(root);
+
+        self.detach(Some(root))
+    }
+}
+
+// splitmix64, good enough to keep the tree balanced whatever order the ids
+// are moved in while keeping the layout the same from run to run.
+fn priority(id: usize) -> u64 {
+    let mut value = (id as u64).wrapping_add(0x9e3779b97f4a7c15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+    value ^ (value >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(list: &IndexedList) -> Vec<usize> {
+        list.iter().collect()
+    }
+
+    #[test]
+    fn test_new() {
+        let list = IndexedList::new(5);
+        assert_eq!(5, list.len());
+        assert_eq!(vec![0, 1, 2, 3, 4], ids(&list));
+        assert_eq!(3, list.position(3));
+        assert_eq!(Some(4), list.get(4));
+        assert_eq!(None, list.get(5));
+    }
+
+    #[test]
+    fn test_move() {
+        let mut list = IndexedList::new(5);
+        list.move_to(0, 3);
+        assert_eq!(vec![1, 2, 3, 0, 4], ids(&list));
+        list.move_to(4, 0);
+        assert_eq!(vec![4, 1, 2, 3, 0], ids(&list));
+
+        list.remove(2);
+        assert_eq!(4, list.len());
+        assert_eq!(vec![4, 1, 3, 0], ids(&list));
+        list.insert(2, 4);
+        assert_eq!(vec![4, 1, 3, 0, 2], ids(&list));
+        assert_eq!(4, list.position(2));
+    }
+
+    #[test]
+    fn test_matches_vec() {
+        // pseudo-random moves, checked against doing the same with a plain vec
+        let mut list = IndexedList::new(200);
+        let mut expected: Vec<usize> = (0..200).collect();
+        let mut seed = 7_u64;
+
+        for _ in 0..2000 {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let id = (seed >> 33) as usize % 200;
+            let position = (seed >> 13) as usize % 200;
+
+            let from = expected.iter().position(|e| *e == id).unwrap();
+            assert_eq!(from, list.position(id));
+            expected.remove(from);
+            expected.insert(position, id);
+            list.move_to(id, position);
+        }
+
+        assert_eq!(expected, ids(&list));
+        assert!((0..200).all(|position| list.get(position) == Some(expected[position])));
+    }
+
+    #[test]
+    fn test_empty() {
+        let mut list = IndexedList::new(1);
+        list.remove(0);
+        assert!(list.is_empty());
+        assert_eq!(0, list.iter().count());
+        assert_eq!(None, list.get(0));
+        assert!(IndexedList::new(0).is_empty());
+    }
+}
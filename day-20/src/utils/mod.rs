@@ -0,0 +1 @@
+pub mod indexed_list;
@@ -1,122 +1,505 @@
-use num::{Complex, Zero};
+use num::{BigInt, BigRational, One, Signed, ToPrimitive, Zero};
 use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+impl Operator {
+    fn apply(&self, left: &BigRational, right: &BigRational) -> Result<BigRational, String> {
+        match self {
+            Operator::Add => Ok(left + right),
+            Operator::Subtract => Ok(left - right),
+            Operator::Multiply => Ok(left * right),
+            Operator::Divide if right.is_zero() => Err("division by zero".to_string()),
+            Operator::Divide => Ok(left / right),
+        }
+    }
+
+    // how tightly the operator binds, multiplication before addition
+    fn precedence(&self) -> u8 {
+        match self {
+            Operator::Add | Operator::Subtract => 1,
+            Operator::Multiply | Operator::Divide => 2,
+        }
+    }
+}
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+        };
+        write!(f, "{}", symbol)
+    }
+}
 
 #[derive(Debug)]
 enum Instruction {
-    Number(f64),
-    Add(String, String),
-    Subtract(String, String),
-    Multiply(String, String),
-    Divide(String, String),
-}
-impl Instruction {
-    fn eval(&self, context: &Monkeys) -> Complex<f64> {
+    Number(BigInt),
+    Operation(Operator, String, String),
+}
+
+// a monkey's job written out in full, with every monkey it depends on replaced
+// by their own expression. numbers are exact rationals so nothing is lost to
+// rounding, and anything not depending on the unknown is folded to a number.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Expr {
+    Number(BigRational),
+    Unknown(String),
+    Operation(Operator, Box<Expr>, Box<Expr>),
+}
+impl Expr {
+    // combines the two sides, folding them into a number if we can
+    fn operation(operator: Operator, left: Expr, right: Expr) -> Result<Expr, String> {
+        match (&left, &right) {
+            (Expr::Number(l), Expr::Number(r)) => Ok(Expr::Number(operator.apply(l, r)?)),
+            _ => Ok(Expr::Operation(operator, Box::new(left), Box::new(right))),
+        }
+    }
+
+    pub fn contains_unknown(&self) -> bool {
+        match self {
+            Expr::Number(_) => false,
+            Expr::Unknown(_) => true,
+            Expr::Operation(_, left, right) => left.contains_unknown() || right.contains_unknown(),
+        }
+    }
+
+    // the expression as `a * unknown + b`, only possible when the unknown is
+    // never multiplied by itself or divided into something.
+    fn linear(&self) -> Result<(BigRational, BigRational), String> {
         match self {
-            Instruction::Number(value) => Complex::new(*value, 0.0),
-            Instruction::Add(left, right) => context.eval(left) + context.eval(right),
-            Instruction::Subtract(left, right) => context.eval(left) - context.eval(right),
-            Instruction::Multiply(left, right) => {
-                let eval_right = context.eval(right);
-                let eval_left = context.eval(left);
-                match (eval_left.im.is_zero(), eval_right.im.is_zero()) {
-                    (true, false) => eval_right.scale(eval_left.re),
-                    (_, true) => eval_left.scale(eval_right.re),
-                    _ => panic!("should not have cases where both sides contain imaginary numbers"),
+            Expr::Number(value) => Ok((BigRational::zero(), value.clone())),
+            Expr::Unknown(_) => Ok((BigRational::one(), BigRational::zero())),
+            Expr::Operation(operator, left, right) => {
+                let (la, lb) = left.linear()?;
+                let (ra, rb) = right.linear()?;
+                match operator {
+                    Operator::Add => Ok((la + ra, lb + rb)),
+                    Operator::Subtract => Ok((la - ra, lb - rb)),
+                    Operator::Multiply if la.is_zero() => Ok((ra * &lb, rb * lb)),
+                    Operator::Multiply if ra.is_zero() => Ok((la * &rb, lb * rb)),
+                    Operator::Divide if ra.is_zero() && !rb.is_zero() => Ok((la / &rb, lb / rb)),
+                    Operator::Divide if ra.is_zero() => Err("division by zero".to_string()),
+                    _ => Err("non-linear: the unknown is multiplied or divided by itself".into()),
                 }
             }
-            Instruction::Divide(left, right) => {
-                let eval_left = context.eval(left);
-                let eval_right = context.eval(right);
-                match (eval_left.im.is_zero(), eval_right.im.is_zero()) {
-                    (_, true) => eval_left.unscale(eval_right.re),
-                    _ => panic!("we don't have cases where the imaginary is on the left"),
+        }
+    }
+
+    // the value of the unknown which makes this expression equal the target.
+    // while the unknown is only on one side of an operation we peel it off by
+    // applying the inverse to the target, if it turns up on both sides that
+    // part of the expression has to be linear.
+    pub fn isolate(&self, target: BigRational) -> Result<BigRational, String> {
+        let mut expr = self;
+        let mut target = target;
+
+        loop {
+            let Expr::Operation(operator, left, right) = expr else {
+                return match expr {
+                    Expr::Unknown(_) => Ok(target),
+                    _ => Err("the equation doesn't depend on the unknown".to_string()),
+                };
+            };
+
+            target = match (left.as_ref(), right.as_ref()) {
+                (Expr::Number(_), Expr::Number(_)) => {
+                    return Err("the equation doesn't depend on the unknown".to_string());
+                }
+                // unknown on the left, ie: x - value = target
+                (_, Expr::Number(value)) => {
+                    expr = left;
+                    match operator {
+                        Operator::Add => target - value,
+                        Operator::Subtract => target + value,
+                        Operator::Multiply if value.is_zero() => {
+                            return Err("no unique solution, the unknown is multiplied by 0".into())
+                        }
+                        Operator::Multiply => target / value,
+                        Operator::Divide if value.is_zero() => {
+                            return Err("division by zero".to_string())
+                        }
+                        Operator::Divide => target * value,
+                    }
+                }
+                // unknown on the right, ie: value - x = target
+                (Expr::Number(value), _) => {
+                    expr = right;
+                    match operator {
+                        Operator::Add => target - value,
+                        Operator::Subtract => value - target,
+                        Operator::Multiply if value.is_zero() => {
+                            return Err("no unique solution, the unknown is multiplied by 0".into())
+                        }
+                        Operator::Multiply => target / value,
+                        Operator::Divide if target.is_zero() => {
+                            return Err("no unique solution, nothing divided by 0".into())
+                        }
+                        Operator::Divide => value / target,
+                    }
+                }
+                // unknown on both sides
+                _ => {
+                    let (a, b) = expr.linear()?;
+                    if a.is_zero() {
+                        return Err("no unique solution, the unknown cancels out".to_string());
+                    }
+                    return Ok((target - b) / a);
+                }
+            };
+        }
+    }
+}
+
+impl Expr {
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Operation(operator, _, _) => operator.precedence(),
+            _ => u8::MAX,
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Expr::Number(value) => value.to_string(),
+            Expr::Unknown(name) => name.clone(),
+            Expr::Operation(operator, _, _) => operator.to_string(),
+        }
+    }
+
+    // the expression as a graphviz digraph, operators pointing at their left
+    // then right operands and the unknown drawn as a box.
+    pub fn to_dot(&self) -> String {
+        let mut lines = vec!["digraph {".to_string()];
+        self.write_dot(&mut lines, &mut 0);
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+
+    fn children(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Operation(_, left, right) => vec![left, right],
+            _ => vec![],
+        }
+    }
+
+    // adds a node for this expression and everything below it, returning the
+    // id of this one.
+    fn write_dot(&self, lines: &mut Vec<String>, next_id: &mut usize) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+
+        let shape = match self {
+            Expr::Unknown(_) => ", shape=box",
+            _ => "",
+        };
+        lines.push(format!(
+            "    n{} [label=\"{}\"{}];",
+            id,
+            self.label(),
+            shape
+        ));
+        for child in self.children() {
+            let child_id = child.write_dot(lines, next_id);
+            lines.push(format!("    n{} -> n{};", id, child_id));
+        }
+
+        id
+    }
+}
+
+// infix with as few brackets as we can get away with, fractions and negative
+// numbers are always bracketed so they read as a single value.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Number(value) if value.is_integer() && !value.is_negative() => {
+                write!(f, "{}", value)
+            }
+            Expr::Number(value) => write!(f, "({})", value),
+            Expr::Unknown(name) => write!(f, "{}", name),
+            Expr::Operation(operator, left, right) => {
+                let precedence = operator.precedence();
+                // a - (b - c) isn't (a - b) - c, so the right side also needs
+                // brackets on a tie for the operators that aren't associative
+                let bracket_right = right.precedence() < precedence
+                    || (right.precedence() == precedence
+                        && matches!(operator, Operator::Subtract | Operator::Divide));
+
+                match left.precedence() < precedence {
+                    true => write!(f, "({})", left)?,
+                    false => write!(f, "{}", left)?,
+                }
+                write!(f, " {} ", operator)?;
+                match bracket_right {
+                    true => write!(f, "({})", right),
+                    false => write!(f, "{}", right),
                 }
             }
         }
     }
 }
 
-#[derive(Debug)]
-struct Monkey {
-    instruction: Instruction,
-    human: bool,
+// the two sides root compares, which have to be equal
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Equation {
+    pub left: Expr,
+    pub right: Expr,
 }
-impl Monkey {
-    fn new(human: bool, instruction: Instruction) -> Monkey {
-        Monkey { instruction, human }
+impl Equation {
+    // the value of the unknown which makes both sides equal
+    pub fn solve(&self) -> Result<BigRational, String> {
+        Expr::operation(Operator::Subtract, self.left.clone(), self.right.clone())?
+            .isolate(BigRational::zero())
     }
 
-    fn eval(&self, context: &Monkeys) -> Complex<f64> {
-        match self.human {
-            true => Complex::i(),
-            false => self.instruction.eval(context),
+    // same as the expressions' dot, joined by an = node
+    pub fn to_dot(&self) -> String {
+        let mut lines = vec!["digraph {".to_string(), "    n0 [label=\"=\"];".to_string()];
+        let mut next_id = 1;
+        for side in [&self.left, &self.right] {
+            let side_id = side.write_dot(&mut lines, &mut next_id);
+            lines.push(format!("    n0 -> n{};", side_id));
         }
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+}
+impl fmt::Display for Equation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.left, self.right)
     }
 }
 
+// the monkeys' jobs, along with a cache of what each one shouts. values are
+// only calculated when asked for, and then only for monkeys that haven't been
+// calculated already, so changing one monkey only recalculates the monkeys
+// which depend on it.
 #[derive(Debug)]
-struct Monkeys(HashMap<String, Monkey>);
+pub struct Monkeys {
+    instructions: HashMap<String, Instruction>,
+    dependents: HashMap<String, Vec<String>>,
+    values: HashMap<String, BigRational>,
+}
 impl Monkeys {
-    fn new(input: &str, parse_human: bool) -> Monkeys {
-        Monkeys(
-            input
-                .lines()
-                .map(|line| {
-                    let (name, operation_str) = line.split_once(": ").unwrap();
-                    let operation = match operation_str.split(' ').collect::<Vec<_>>().as_slice() {
-                        [value] => Instruction::Number(value.parse().unwrap()),
-                        [left, "+", right] => Instruction::Add(left.to_string(), right.to_string()),
-                        [left, "-", right] => {
-                            Instruction::Subtract(left.to_string(), right.to_string())
-                        }
-                        [left, "*", right] => {
-                            Instruction::Multiply(left.to_string(), right.to_string())
-                        }
-                        [left, "/", right] => {
-                            Instruction::Divide(left.to_string(), right.to_string())
-                        }
-                        _ => panic!(),
-                    };
-                    let monkey = Monkey::new(parse_human && name.eq("humn"), operation);
-                    (name.to_string(), monkey)
-                })
-                .collect(),
-        )
-    }
-
-    fn eval(&self, name: &str) -> Complex<f64> {
-        let monkey = self.0.get(name).unwrap();
-        monkey.eval(self)
-    }
-}
-
-pub fn part_one(input: &str) -> f64 {
-    let monkeys = Monkeys::new(input, false);
-    monkeys.eval("root").re
-}
-
-pub fn part_two(input: &str) -> f64 {
-    let monkeys = Monkeys::new(input, true);
-    let root = monkeys.0.get("root").unwrap();
-    match &root.instruction {
-        Instruction::Add(left, right) => {
-            let left_monkey = monkeys.0.get(left).unwrap();
-            let right_monkey = monkeys.0.get(right).unwrap();
-            let left_eval = left_monkey.eval(&monkeys);
-            let right_eval = right_monkey.eval(&monkeys);
-
-            match (left_eval.im.is_zero(), right_eval.im.is_zero()) {
-                (true, false) => ((left_eval.re - right_eval.re) / right_eval.im).round(),
-                (false, true) => ((right_eval.re - left_eval.re) / left_eval.im).round(),
-                _ => panic!(
-                    "expected one imaginary number in either the left or right complex number"
-                ),
+    pub fn new(input: &str) -> Monkeys {
+        let instructions: HashMap<String, Instruction> = input
+            .lines()
+            .map(|line| {
+                let (name, operation_str) = line.split_once(": ").unwrap();
+                let operation = match operation_str.split(' ').collect::<Vec<_>>().as_slice() {
+                    [value] => Instruction::Number(value.parse().unwrap()),
+                    [left, operator, right] => {
+                        let operator = match *operator {
+                            "+" => Operator::Add,
+                            "-" => Operator::Subtract,
+                            "*" => Operator::Multiply,
+                            "/" => Operator::Divide,
+                            _ => panic!("unknown operator {}", operator),
+                        };
+                        Instruction::Operation(operator, left.to_string(), right.to_string())
+                    }
+                    _ => panic!("couldn't parse {}", line),
+                };
+                (name.to_string(), operation)
+            })
+            .collect();
+
+        let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
+        for (name, instruction) in &instructions {
+            if let Instruction::Operation(_, left, right) = instruction {
+                for dependency in [left, right] {
+                    dependents
+                        .entry(dependency.clone())
+                        .or_default()
+                        .push(name.clone());
+                }
+            }
+        }
+
+        Monkeys {
+            instructions,
+            dependents,
+            values: HashMap::new(),
+        }
+    }
+
+    fn instruction(&self, name: &str) -> Result<&Instruction, String> {
+        self.instructions
+            .get(name)
+            .ok_or_else(|| format!("no monkey named {}", name))
+    }
+
+    // the named monkey and every monkey it depends on, ordered so that each
+    // one comes after everything it depends on. monkeys which are known aren't
+    // looked into any further. errors if a monkey isn't defined or ends up
+    // depending on itself.
+    fn dependency_order<'a, F>(&'a self, name: &'a str, is_known: F) -> Result<Vec<&'a str>, String>
+    where
+        F: Fn(&str) -> bool,
+    {
+        // monkeys which are entered but not yet exited are exactly the ones on
+        // the path to the current monkey, finding one again means a cycle.
+        let mut finished: HashMap<&str, bool> = HashMap::new();
+        let mut order = vec![];
+        let mut stack = vec![(name, false)];
+
+        while let Some((current, exiting)) = stack.pop() {
+            if exiting {
+                finished.insert(current, true);
+                order.push(current);
+                continue;
+            }
+
+            match finished.get(current) {
+                Some(true) => continue,
+                Some(false) => return Err(format!("{} depends on itself", current)),
+                None => (),
             }
+
+            if is_known(current) {
+                finished.insert(current, true);
+                order.push(current);
+                continue;
+            }
+
+            let (key, instruction) = self
+                .instructions
+                .get_key_value(current)
+                .ok_or_else(|| format!("no monkey named {}", current))?;
+            finished.insert(key, false);
+            stack.push((key, true));
+            if let Instruction::Operation(_, left, right) = instruction {
+                stack.push((right.as_str(), false));
+                stack.push((left.as_str(), false));
+            }
+        }
+
+        Ok(order)
+    }
+
+    // what the named monkey shouts, calculating (and remembering) only the
+    // monkeys we don't already know the value of.
+    pub fn value(&mut self, name: &str) -> Result<BigRational, String> {
+        let order: Vec<String> = self
+            .dependency_order(name, |monkey| self.values.contains_key(monkey))?
+            .into_iter()
+            .map(|monkey| monkey.to_string())
+            .collect();
+
+        for monkey in order {
+            if self.values.contains_key(&monkey) {
+                continue;
+            }
+
+            let value = match self.instruction(&monkey)? {
+                Instruction::Number(value) => BigRational::from(value.clone()),
+                Instruction::Operation(operator, left, right) => {
+                    operator.apply(&self.values[left], &self.values[right])?
+                }
+            };
+            self.values.insert(monkey, value);
+        }
+
+        Ok(self.values[name].clone())
+    }
+
+    // changes what the monkey shouts, forgetting the values of everything
+    // which depended on it so they're recalculated next time they're needed.
+    pub fn set_value(&mut self, name: &str, value: i64) {
+        self.instructions
+            .insert(name.to_string(), Instruction::Number(value.into()));
+
+        // a monkey can only have a value if everything it depends on does,
+        // so we can stop as soon as we reach one without a value.
+        let mut stale = vec![name.to_string()];
+        while let Some(monkey) = stale.pop() {
+            if self.values.remove(&monkey).is_some() || monkey == name {
+                if let Some(dependents) = self.dependents.get(&monkey) {
+                    stale.extend(dependents.iter().cloned());
+                }
+            }
+        }
+    }
+
+    // the expression for the named monkey, with the unknown monkey (if any)
+    // left as a symbol rather than using the number it shouts.
+    fn expr(&self, name: &str, unknown: Option<&str>) -> Result<Expr, String> {
+        let mut exprs: HashMap<&str, Expr> = HashMap::new();
+
+        for monkey in self.dependency_order(name, |monkey| Some(monkey) == unknown)? {
+            let expr = match self.instruction(monkey) {
+                _ if Some(monkey) == unknown => Expr::Unknown(monkey.to_string()),
+                Ok(Instruction::Number(value)) => Expr::Number(BigRational::from(value.clone())),
+                Ok(Instruction::Operation(operator, left, right)) => Expr::operation(
+                    *operator,
+                    exprs[left.as_str()].clone(),
+                    exprs[right.as_str()].clone(),
+                )?,
+                Err(err) => return Err(err),
+            };
+            exprs.insert(monkey, expr);
         }
-        _ => panic!("root didn't have an add operation"),
+
+        Ok(exprs.remove(name).unwrap())
     }
 }
 
+// what the named monkey shouts
+pub fn evaluate(input: &str, name: &str) -> Result<BigRational, String> {
+    Monkeys::new(input).value(name)
+}
+
+// the named monkey's expression, with everything not depending on the unknown
+// (if there is one) folded down to a number.
+pub fn expression(input: &str, name: &str, unknown: Option<&str>) -> Result<Expr, String> {
+    Monkeys::new(input).expr(name, unknown)
+}
+
+// what root's two inputs are in terms of the unknown, whatever operation root
+// would normally do they have to be equal.
+pub fn equation(input: &str, root: &str, unknown: &str) -> Result<Equation, String> {
+    let monkeys = Monkeys::new(input);
+    let Instruction::Operation(_, left, right) = monkeys.instruction(root)? else {
+        return Err(format!("{} doesn't compare two monkeys", root));
+    };
+
+    Ok(Equation {
+        left: monkeys.expr(left, Some(unknown))?,
+        right: monkeys.expr(right, Some(unknown))?,
+    })
+}
+
+// the number the unknown monkey has to shout for both of the root monkey's
+// inputs to be equal. the unknown can be on either side, or both as long as
+// it's linear.
+pub fn solve(input: &str, root: &str, unknown: &str) -> Result<BigRational, String> {
+    equation(input, root, unknown)?.solve()
+}
+
+fn to_i64(value: BigRational) -> i64 {
+    match value.is_integer() {
+        true => value.to_integer().to_i64().unwrap(),
+        false => panic!("{} isn't a whole number", value),
+    }
+}
+
+pub fn part_one(input: &str) -> i64 {
+    to_i64(evaluate(input, "root").unwrap())
+}
+
+pub fn part_two(input: &str) -> i64 {
+    to_i64(solve(input, "root", "humn").unwrap())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,23 +507,171 @@ mod tests {
     static EXAMPLE_INPUT: &str = include_str!("../input.example.txt");
     static INPUT: &str = include_str!("../input.txt");
 
+    fn ratio(numer: i64, denom: i64) -> BigRational {
+        BigRational::new(numer.into(), denom.into())
+    }
+
     #[test]
     fn test_part_one_example() {
-        assert_eq!(152.0, part_one(EXAMPLE_INPUT));
+        assert_eq!(152, part_one(EXAMPLE_INPUT));
     }
 
     #[test]
     fn test_part_one() {
-        assert_eq!(282285213953670.0, part_one(INPUT));
+        assert_eq!(282285213953670, part_one(INPUT));
     }
 
     #[test]
     fn test_part_two_example() {
-        assert_eq!(301.0, part_two(EXAMPLE_INPUT));
+        assert_eq!(301, part_two(EXAMPLE_INPUT));
     }
 
     #[test]
     fn test_part_two() {
-        assert_eq!(3699945358564.0, part_two(INPUT));
+        assert_eq!(3699945358564, part_two(INPUT));
+    }
+
+    #[test]
+    fn test_equation() {
+        let example = equation(EXAMPLE_INPUT, "root", "humn").unwrap();
+        assert_eq!("(4 + 2 * (humn - 3)) / 4 = 150", example.to_string());
+        assert_eq!(Ok(ratio(301, 1)), example.solve());
+
+        let input = "root: a + b\na: c - d\nc: 10\nd: e - humn\ne: 7\nb: f / humn\nf: 3";
+        assert_eq!(
+            "10 - (7 - humn) = 3 / humn",
+            equation(input, "root", "humn").unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn test_expression() {
+        let folded = expression(EXAMPLE_INPUT, "root", None).unwrap();
+        assert_eq!("152", folded.to_string());
+
+        let expr = expression(EXAMPLE_INPUT, "lgvd", Some("humn")).unwrap();
+        assert_eq!("2 * (humn - 3)", expr.to_string());
+
+        let expr = expression(EXAMPLE_INPUT, "pppw", Some("hmdt")).unwrap();
+        assert_eq!(Expr::Number(ratio(2, 1)), expr);
+
+        // fractions and negatives stay readable
+        let input = "root: a * humn\na: b / c\nb: 3\nc: -2";
+        let expr = expression(input, "root", Some("humn")).unwrap();
+        assert_eq!("(-3/2) * humn", expr.to_string());
+    }
+
+    #[test]
+    fn test_dot() {
+        let expr = expression(EXAMPLE_INPUT, "lgvd", Some("humn")).unwrap();
+        assert_eq!(
+            [
+                "digraph {",
+                "    n0 [label=\"*\"];",
+                "    n1 [label=\"2\"];",
+                "    n0 -> n1;",
+                "    n2 [label=\"-\"];",
+                "    n3 [label=\"humn\", shape=box];",
+                "    n2 -> n3;",
+                "    n4 [label=\"3\"];",
+                "    n2 -> n4;",
+                "    n0 -> n2;",
+                "}",
+            ]
+            .join("\n"),
+            expr.to_dot()
+        );
+
+        let dot = equation(EXAMPLE_INPUT, "root", "humn").unwrap().to_dot();
+        assert!(dot.starts_with("digraph {\n    n0 [label=\"=\"];"));
+        assert_eq!(10, dot.matches(" -> ").count());
+    }
+
+    #[test]
+    fn test_incremental_value() {
+        let mut monkeys = Monkeys::new(EXAMPLE_INPUT);
+        assert_eq!(Ok(ratio(152, 1)), monkeys.value("root"));
+        assert_eq!(15, monkeys.values.len());
+
+        // only humn and the five monkeys between it and root are forgotten
+        monkeys.set_value("humn", 301);
+        assert_eq!(9, monkeys.values.len());
+        assert_eq!(Ok(ratio(300, 1)), monkeys.value("root"));
+        assert_eq!(Ok(ratio(150, 1)), monkeys.value("sjmn"));
+        assert_eq!(15, monkeys.values.len());
+    }
+
+    #[test]
+    fn test_value_errors() {
+        let mut monkeys = Monkeys::new("root: a + b\na: b * c\nb: c - a\nc: 1");
+        assert!(monkeys
+            .value("root")
+            .unwrap_err()
+            .contains("depends on itself"));
+        assert_eq!(Ok(ratio(1, 1)), monkeys.value("c"));
+        assert!(solve("root: a + b\na: b * humn\nb: a - c\nc: 1", "root", "humn").is_err());
+
+        let mut monkeys = Monkeys::new("root: a + b\na: 5\nb: a / c\nc: 0");
+        assert_eq!(Err("division by zero".to_string()), monkeys.value("root"));
+        assert_eq!(Err("no monkey named z".to_string()), monkeys.value("z"));
+    }
+
+    #[test]
+    fn test_deep_chain() {
+        // far deeper than we could recurse
+        let mut input = vec!["m0: 1".to_string(), "one: 1".to_string()];
+        input.extend((1..50_000).map(|i| format!("m{}: m{} + one", i, i - 1)));
+        let mut monkeys = Monkeys::new(&input.join("\n"));
+        assert_eq!(Ok(ratio(50_000, 1)), monkeys.value("m49999"));
+    }
+
+    #[test]
+    fn test_solve_either_side() {
+        // unknown on the right, and root's own operation doesn't matter
+        let swapped = EXAMPLE_INPUT.replace("root: pppw + sjmn", "root: sjmn * pppw");
+        assert_eq!(Ok(ratio(301, 1)), solve(&swapped, "root", "humn"));
+
+        // unknown on both sides: 2x = x + 3
+        let input = "root: a + b\na: humn * two\nb: humn + three\nhumn: 1\ntwo: 2\nthree: 3";
+        assert_eq!(Ok(ratio(3, 1)), solve(input, "root", "humn"));
+
+        // unknown as the divisor: 12 / x = 4
+        let input = "root: a + b\na: c / humn\nc: 12\nb: 4\nhumn: 1";
+        assert_eq!(Ok(ratio(3, 1)), solve(input, "root", "humn"));
+    }
+
+    #[test]
+    fn test_solve_exact() {
+        // 3 - x * 2 = 0
+        let input = "root: a + b\na: humn * two\nb: 3\nhumn: 1\ntwo: 2";
+        assert_eq!(Ok(ratio(3, 2)), solve(input, "root", "humn"));
+
+        // any monkey can be the unknown: pppw is 2, so (hmdt - 2) * 5 = 2
+        assert_eq!(Ok(ratio(12, 5)), solve(EXAMPLE_INPUT, "root", "hmdt"));
+    }
+
+    #[test]
+    fn test_solve_errors() {
+        let input = "root: a + b\na: humn * humn\nb: 4\nhumn: 1";
+        assert!(solve(input, "root", "humn")
+            .unwrap_err()
+            .contains("non-linear"));
+
+        let input = "root: a + b\na: humn - humn\nb: 4\nhumn: 1";
+        assert!(solve(input, "root", "humn")
+            .unwrap_err()
+            .contains("no unique solution"));
+
+        let input = "root: a + b\na: humn * zero\nb: 4\nhumn: 1\nzero: 0";
+        assert!(solve(input, "root", "humn").is_err());
+
+        let input = "root: a + b\na: 2\nb: 4";
+        assert!(solve(input, "root", "humn").is_err());
+
+        let input = "root: a + b\na: humn + humn\nb: c - humn";
+        assert_eq!(
+            Err("no monkey named c".to_string()),
+            solve(input, "root", "humn")
+        );
     }
 }
@@ -1,6 +1,8 @@
+pub mod utils;
 use core::fmt;
 use std::collections::hash_map::Entry::Vacant;
 use std::collections::{HashMap, HashSet};
+use utils::rect::Rect;
 
 // our core model for the location in grid, effectively a tuple with utility.
 #[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
@@ -152,13 +154,9 @@ fn solve(input: &str, part: Part) -> i32 {
 
         match part {
             Part::One if turn == 10 => {
-                let elves_vec: Vec<_> = elves.into_iter().collect();
-                let min_x = elves_vec.iter().map(|location| location.x).min().unwrap();
-                let max_x = elves_vec.iter().map(|location| location.x).max().unwrap();
-                let min_y = elves_vec.iter().map(|location| location.y).min().unwrap();
-                let max_y = elves_vec.iter().map(|location| location.y).max().unwrap();
-
-                return ((max_x - min_x + 1) * (max_y - min_y + 1)) - elves_vec.len() as i32;
+                let bounds =
+                    Rect::from_points(elves.iter().map(|location| (location.x, location.y)));
+                return bounds.area() as i32 - elves.len() as i32;
             }
             Part::Two if done_count == elves.len() => {
                 return turn as i32;
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// describes a repeating section of a simulation. once the state seen at step
+// `start` re-appears `length` steps later the simulation will repeat forever,
+// with the tracked value increasing by `delta` on every repetition.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+    pub delta: i64,
+}
+
+// records the state of a simulation at each step, keyed by anything hashable
+// which fully describes the state, along with a value we care about (ie: a
+// height or a score) so that it can be extrapolated once a cycle is found.
+#[derive(Debug)]
+pub struct CycleDetector<K> {
+    seen: HashMap<K, usize>,
+    history: Vec<i64>,
+}
+
+impl<K: Hash + Eq> CycleDetector<K> {
+    pub fn new() -> CycleDetector<K> {
+        CycleDetector {
+            seen: HashMap::new(),
+            history: Vec::new(),
+        }
+    }
+
+    // number of steps recorded so far, the next call to record is this step
+    pub fn steps(&self) -> usize {
+        self.history.len()
+    }
+
+    // records the state and value for the next step, returns the cycle as soon
+    // as a previously recorded state is recorded again.
+    pub fn record(&mut self, state: K, value: i64) -> Option<Cycle> {
+        let step = self.history.len();
+        self.history.push(value);
+
+        match self.seen.insert(state, step) {
+            Some(start) => Some(Cycle {
+                start,
+                length: step - start,
+                delta: value - self.history[start],
+            }),
+            None => None,
+        }
+    }
+
+    // value at any step, either straight from the history or extrapolated from
+    // the cycle if we haven't simulated that far
+    pub fn value_at(&self, cycle: &Cycle, step: usize) -> i64 {
+        if step < self.history.len() {
+            return self.history[step];
+        }
+
+        let offset = step - cycle.start;
+        let repetitions = (offset / cycle.length) as i64;
+        self.history[cycle.start + (offset % cycle.length)] + (repetitions * cycle.delta)
+    }
+}
+
+impl<K: Hash + Eq> Default for CycleDetector<K> {
+    fn default() -> Self {
+        CycleDetector::new()
+    }
+}
+
+// drives a simulation until either the target step is reached or a cycle is
+// found, then jumps straight to the value at the target. the simulate function
+// is called with each step number (starting from 0) and returns the state and
+// value at that step.
+pub fn fast_forward<K, F>(target: usize, mut simulate: F) -> i64
+where
+    K: Hash + Eq,
+    F: FnMut(usize) -> (K, i64),
+{
+    let mut detector = CycleDetector::new();
+
+    for step in 0..=target {
+        let (state, value) = simulate(step);
+        if let Some(cycle) = detector.record(state, value) {
+            return detector.value_at(&cycle, target);
+        }
+    }
+
+    detector.history[target]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_cycle() {
+        let mut detector = CycleDetector::new();
+        // states: a b c d b c d ... values grow by 10 every loop
+        assert_eq!(None, detector.record('a', 0));
+        assert_eq!(None, detector.record('b', 1));
+        assert_eq!(None, detector.record('c', 3));
+        assert_eq!(None, detector.record('d', 6));
+
+        let cycle = detector.record('b', 11).unwrap();
+        assert_eq!(
+            Cycle {
+                start: 1,
+                length: 3,
+                delta: 10
+            },
+            cycle
+        );
+        assert_eq!(5, detector.steps());
+
+        assert_eq!(6, detector.value_at(&cycle, 3));
+        assert_eq!(13, detector.value_at(&cycle, 5));
+        assert_eq!(16, detector.value_at(&cycle, 6));
+        assert_eq!(21, detector.value_at(&cycle, 7));
+        assert_eq!(1 + 10 * 1000, detector.value_at(&cycle, 3001));
+    }
+
+    #[test]
+    fn test_fast_forward() {
+        // x -> x * 3 mod 7 cycles through 6 states, count each step as a point
+        let mut current = 1;
+        let value = fast_forward(1_000_000_000, |step| {
+            if step > 0 {
+                current = current * 3 % 7;
+            }
+            (current, step as i64)
+        });
+        assert_eq!(1_000_000_000, value);
+    }
+
+    #[test]
+    fn test_fast_forward_without_cycle() {
+        assert_eq!(45, fast_forward(9, |step| (step, (0..=step as i64).sum())));
+    }
+}
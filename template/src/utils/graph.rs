@@ -0,0 +1,236 @@
+use std::collections::{HashMap, VecDeque};
+
+// distances between every pair of nodes, none when there's no path
+pub type Distances = Vec<Vec<Option<usize>>>;
+
+// unweighted directed graph where nodes are referred to by name when building
+// and by index afterwards, so that lookups are plain vec accesses.
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    names: Vec<String>,
+    indices: HashMap<String, usize>,
+    adjacency: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    pub fn new() -> Graph {
+        Graph {
+            names: Vec::new(),
+            indices: HashMap::new(),
+            adjacency: Vec::new(),
+        }
+    }
+
+    pub fn from_edges<I, S>(edges: I) -> Graph
+    where
+        I: IntoIterator<Item = (S, S)>,
+        S: AsRef<str>,
+    {
+        let mut graph = Graph::new();
+        edges
+            .into_iter()
+            .for_each(|(from, to)| graph.add_edge(from.as_ref(), to.as_ref()));
+        graph
+    }
+
+    // returns the index for the name, adding the node if we haven't seen it
+    pub fn intern(&mut self, name: &str) -> usize {
+        if let Some(index) = self.indices.get(name) {
+            return *index;
+        }
+
+        let index = self.names.len();
+        self.names.push(name.to_string());
+        self.indices.insert(name.to_string(), index);
+        self.adjacency.push(Vec::new());
+        index
+    }
+
+    pub fn add_edge(&mut self, from: &str, to: &str) {
+        let from = self.intern(from);
+        let to = self.intern(to);
+        if !self.adjacency[from].contains(&to) {
+            self.adjacency[from].push(to);
+        }
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.indices.get(name).copied()
+    }
+
+    pub fn name(&self, index: usize) -> &str {
+        &self.names[index]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn neighbors(&self, index: usize) -> &[usize] {
+        &self.adjacency[index]
+    }
+
+    // distance from the node to every other node
+    pub fn bfs(&self, from: usize) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.len()];
+        let mut queue = VecDeque::from([from]);
+        distances[from] = Some(0);
+
+        while let Some(current) = queue.pop_front() {
+            let distance = distances[current].unwrap() + 1;
+            for next in &self.adjacency[current] {
+                if distances[*next].is_none() {
+                    distances[*next] = Some(distance);
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        distances
+    }
+
+    // all pairs shortest distances by running a bfs from every node, the
+    // better choice for sparse graphs like ours.
+    pub fn distances(&self) -> Distances {
+        (0..self.len()).map(|from| self.bfs(from)).collect()
+    }
+
+    // all pairs shortest distances in O(n^3), gives the same answer as
+    // distances but doesn't care how dense the graph is.
+    pub fn floyd_warshall(&self) -> Distances {
+        let size = self.len();
+        let mut distances: Distances = vec![vec![None; size]; size];
+        for (from, row) in distances.iter_mut().enumerate() {
+            row[from] = Some(0);
+            for to in &self.adjacency[from] {
+                if *to != from {
+                    row[*to] = Some(1);
+                }
+            }
+        }
+
+        for via in 0..size {
+            let from_via = distances[via].clone();
+            for row in distances.iter_mut() {
+                let Some(to_via) = row[via] else {
+                    continue;
+                };
+                for (to, distance) in from_via.iter().enumerate() {
+                    if let Some(distance) = distance {
+                        let through = to_via + distance;
+                        match row[to] {
+                            Some(current) if current <= through => (),
+                            _ => row[to] = Some(through),
+                        }
+                    }
+                }
+            }
+        }
+
+        distances
+    }
+
+    // every node visited on a shortest path, including both ends
+    pub fn shortest_path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        let mut previous: Vec<Option<usize>> = vec![None; self.len()];
+        let mut queue = VecDeque::from([from]);
+        previous[from] = Some(from);
+
+        while let Some(current) = queue.pop_front() {
+            if current == to {
+                let mut path = vec![to];
+                while *path.last().unwrap() != from {
+                    path.push(previous[*path.last().unwrap()].unwrap());
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for next in &self.adjacency[current] {
+                if previous[*next].is_none() {
+                    previous[*next] = Some(current);
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        None
+    }
+
+    // distances between only the nodes we care about, indexed by their
+    // position in `keep` rather than their index in the graph. everything in
+    // between is squashed into the distance.
+    pub fn compress(&self, keep: &[usize]) -> Distances {
+        keep.iter()
+            .map(|from| {
+                let distances = self.bfs(*from);
+                keep.iter().map(|to| distances[*to]).collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A - B - C - D, plus a one way shortcut A -> D and an island E
+    fn graph() -> Graph {
+        let mut graph = Graph::from_edges([
+            ("A", "B"),
+            ("B", "A"),
+            ("B", "C"),
+            ("C", "B"),
+            ("C", "D"),
+            ("D", "C"),
+            ("A", "D"),
+        ]);
+        graph.intern("E");
+        graph
+    }
+
+    #[test]
+    fn test_interning() {
+        let mut graph = graph();
+        assert_eq!(5, graph.len());
+        assert_eq!(Some(2), graph.index("C"));
+        assert_eq!("C", graph.name(2));
+        assert_eq!(2, graph.intern("C"));
+        assert_eq!(None, graph.index("Z"));
+        assert_eq!(&[1, 3], graph.neighbors(0));
+    }
+
+    #[test]
+    fn test_distances() {
+        let graph = graph();
+        let bfs = graph.distances();
+        assert_eq!(bfs, graph.floyd_warshall());
+        assert_eq!(Some(1), bfs[0][3]);
+        assert_eq!(Some(3), bfs[3][0]);
+        assert_eq!(None, bfs[0][4]);
+        assert_eq!(Some(0), bfs[4][4]);
+    }
+
+    #[test]
+    fn test_shortest_path() {
+        let graph = graph();
+        assert_eq!(Some(vec![3, 2, 1, 0]), graph.shortest_path(3, 0));
+        assert_eq!(Some(vec![0, 3]), graph.shortest_path(0, 3));
+        assert_eq!(Some(vec![2]), graph.shortest_path(2, 2));
+        assert_eq!(None, graph.shortest_path(0, 4));
+    }
+
+    #[test]
+    fn test_compress() {
+        let graph = graph();
+        let compressed = graph.compress(&[3, 0]);
+        assert_eq!(
+            vec![vec![Some(0), Some(3)], vec![Some(1), Some(0)]],
+            compressed
+        );
+    }
+}
@@ -1,5 +1,8 @@
 use std::collections::HashMap;
 
+use super::coord::Coord2;
+use super::rect::Rect;
+
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Coordinate(pub i32, pub i32);
 
@@ -21,6 +24,18 @@ impl Coordinate {
     }
 }
 
+impl From<Coord2> for Coordinate {
+    fn from(coord: Coord2) -> Coordinate {
+        Coordinate(coord[0], coord[1])
+    }
+}
+
+impl From<Coordinate> for Coord2 {
+    fn from(coordinate: Coordinate) -> Coord2 {
+        Coord2::new([coordinate.0, coordinate.1])
+    }
+}
+
 impl std::fmt::Display for Coordinate {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("Coordinate({},{})", self.0, self.1))
@@ -111,10 +126,7 @@ pub mod directions {
 #[derive(Debug)]
 pub struct Grid<P: Point> {
     points: HashMap<Coordinate, P>,
-    pub min_width: i32,
-    pub min_height: i32,
-    pub max_width: i32,
-    pub max_height: i32,
+    pub bounds: Rect,
 }
 
 pub trait Point {
@@ -135,45 +147,31 @@ impl<P: Point> Grid<P> {
     where
         F: Fn(Coordinate, char) -> P,
     {
-        let mut max_width = 0;
-        let mut max_height = 0;
+        let mut bounds = Rect::new(0, 0, 0, 0);
         let mut points: HashMap<Coordinate, P> = HashMap::new();
         input.lines().enumerate().for_each(|(line_index, line)| {
             line.chars().enumerate().for_each(|(char_index, symbol)| {
                 let coord = Coordinate(char_index as i32, line_index as i32);
                 let point = creator(coord, symbol);
                 if !point.ignore() {
-                    max_width = max_width.max(coord.0);
-                    max_height = max_height.max(coord.1);
+                    bounds.include((coord.0, coord.1));
                     points.insert(coord, creator(coord, symbol));
                 }
             })
         });
-        Grid {
-            points,
-            min_width: 0,
-            min_height: 0,
-            max_width,
-            max_height,
-        }
+        Grid { points, bounds }
     }
 
     pub fn new() -> Grid<P> {
         Grid {
             points: HashMap::new(),
-            min_width: i32::MAX,
-            min_height: i32::MAX,
-            max_width: i32::MIN,
-            max_height: i32::MIN,
+            bounds: Rect::empty(),
         }
     }
 
     pub fn insert(&mut self, point: P) {
         let at = point.coord();
-        self.min_width = self.min_width.min(at.0);
-        self.min_height = self.min_height.min(at.1);
-        self.max_width = self.max_width.max(at.0);
-        self.max_height = self.max_height.max(at.1);
+        self.bounds.include((at.0, at.1));
         self.points.insert(at, point);
     }
 
@@ -203,10 +201,7 @@ impl<P: Point> Grid<P> {
     }
 
     pub fn out_of_bounds(&self, coordinate: Coordinate) -> bool {
-        coordinate.0 > self.max_width
-            || coordinate.0 < self.min_width
-            || coordinate.1 > self.max_height
-            || coordinate.1 < self.min_height
+        !self.bounds.contains((coordinate.0, coordinate.1))
     }
 
     // scans from a starting point, in a direction, and returns the first point found
@@ -230,9 +225,9 @@ impl<P: Point> Grid<P> {
     where
         R: Fn(&P) -> &str,
     {
-        let string_list: Vec<String> = (self.min_height..=self.max_height)
+        let string_list: Vec<String> = (self.bounds.min_y..=self.bounds.max_y)
             .map(|y| {
-                (self.min_width..=self.max_width)
+                (self.bounds.min_x..=self.bounds.max_x)
                     .map(|x| match self.at(&Coordinate(x, y)) {
                         Some(point) => render(point),
                         None => " ",
@@ -248,6 +243,12 @@ impl<P: Point> Grid<P> {
     }
 }
 
+impl<P: Point> Default for Grid<P> {
+    fn default() -> Self {
+        Grid::new()
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct BasicPoint {
     coord: Coordinate,
@@ -335,6 +336,25 @@ mod tests {
         assert_eq!(GRID_STR.trim(), grid.pretty_print());
     }
 
+    #[test]
+    fn test_grid_bounds() {
+        let mut grid = Grid::from(GRID_STR, BasicPoint::new);
+        assert_eq!(Rect::new(0, 0, 6, 2), grid.bounds);
+        assert!(grid.out_of_bounds(Coordinate(7, 0)));
+
+        grid.insert(BasicPoint::new(Coordinate(-1, 4), 'V'));
+        assert_eq!(Rect::new(-1, 0, 6, 4), grid.bounds);
+        assert!(!grid.out_of_bounds(Coordinate(-1, 3)));
+    }
+
+    #[test]
+    fn test_coordinate_conversion() {
+        let coord: Coord2 = Coordinate(3, -4).into();
+        assert_eq!(Coord2::new([3, -4]), coord);
+        let coordinate: Coordinate = coord.into();
+        assert_eq!(Coordinate(3, -4), coordinate);
+    }
+
     #[test]
     fn test_point_distance() {
         assert_eq!(
@@ -1 +1,8 @@
+pub mod coord;
+pub mod cycle;
+pub mod graph;
 pub mod grid;
+pub mod indexed_list;
+pub mod interval;
+pub mod rect;
+pub mod search;
@@ -0,0 +1,382 @@
+// inclusive bounding box over a 2d integer plane. an "empty" rect has its
+// minimums above its maximums, which lets us start from nothing and grow the
+// box one point at a time without special casing the first point.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Rect {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+}
+
+impl Rect {
+    pub fn new(min_x: i32, min_y: i32, max_x: i32, max_y: i32) -> Rect {
+        Rect {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        }
+    }
+
+    pub fn empty() -> Rect {
+        Rect::new(i32::MAX, i32::MAX, i32::MIN, i32::MIN)
+    }
+
+    // smallest rect which contains every provided point, empty if there are none
+    pub fn from_points<I>(points: I) -> Rect
+    where
+        I: IntoIterator,
+        I::Item: Into<(i32, i32)>,
+    {
+        let mut rect = Rect::empty();
+        points.into_iter().for_each(|point| rect.include(point));
+        rect
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_x > self.max_x || self.min_y > self.max_y
+    }
+
+    pub fn width(&self) -> i32 {
+        if self.is_empty() {
+            0
+        } else {
+            self.max_x - self.min_x + 1
+        }
+    }
+
+    pub fn height(&self) -> i32 {
+        if self.is_empty() {
+            0
+        } else {
+            self.max_y - self.min_y + 1
+        }
+    }
+
+    pub fn area(&self) -> i64 {
+        self.width() as i64 * self.height() as i64
+    }
+
+    pub fn contains(&self, point: impl Into<(i32, i32)>) -> bool {
+        let (x, y) = point.into();
+        x >= self.min_x && x <= self.max_x && y >= self.min_y && y <= self.max_y
+    }
+
+    // grows this rect, if needed, so that it contains the point
+    pub fn include(&mut self, point: impl Into<(i32, i32)>) {
+        let (x, y) = point.into();
+        self.min_x = self.min_x.min(x);
+        self.min_y = self.min_y.min(y);
+        self.max_x = self.max_x.max(x);
+        self.max_y = self.max_y.max(y);
+    }
+
+    // returns a new rect grown by amount on every side, negative values shrink
+    pub fn expand(&self, amount: i32) -> Rect {
+        if self.is_empty() {
+            return *self;
+        }
+        Rect::new(
+            self.min_x - amount,
+            self.min_y - amount,
+            self.max_x + amount,
+            self.max_y + amount,
+        )
+    }
+
+    pub fn intersects(&self, other: &Rect) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    pub fn intersection(&self, other: &Rect) -> Rect {
+        let rect = Rect::new(
+            self.min_x.max(other.min_x),
+            self.min_y.max(other.min_y),
+            self.max_x.min(other.max_x),
+            self.max_y.min(other.max_y),
+        );
+        if rect.is_empty() {
+            Rect::empty()
+        } else {
+            rect
+        }
+    }
+
+    // smallest rect containing both rects, empty rects are ignored
+    pub fn union(&self, other: &Rect) -> Rect {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        Rect::new(
+            self.min_x.min(other.min_x),
+            self.min_y.min(other.min_y),
+            self.max_x.max(other.max_x),
+            self.max_y.max(other.max_y),
+        )
+    }
+
+    // iterates over every point in the rect, row by row from the top left
+    pub fn iter(&self) -> impl Iterator<Item = (i32, i32)> {
+        let (min_x, max_x) = (self.min_x, self.max_x);
+        (self.min_y..=self.max_y).flat_map(move |y| (min_x..=max_x).map(move |x| (x, y)))
+    }
+}
+
+impl Default for Rect {
+    fn default() -> Self {
+        Rect::empty()
+    }
+}
+
+// 3d counterpart to rect, same inclusive semantics and same notion of empty
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Cuboid {
+    pub min_x: i32,
+    pub min_y: i32,
+    pub min_z: i32,
+    pub max_x: i32,
+    pub max_y: i32,
+    pub max_z: i32,
+}
+
+impl Cuboid {
+    pub fn new(min: (i32, i32, i32), max: (i32, i32, i32)) -> Cuboid {
+        Cuboid {
+            min_x: min.0,
+            min_y: min.1,
+            min_z: min.2,
+            max_x: max.0,
+            max_y: max.1,
+            max_z: max.2,
+        }
+    }
+
+    pub fn empty() -> Cuboid {
+        Cuboid::new(
+            (i32::MAX, i32::MAX, i32::MAX),
+            (i32::MIN, i32::MIN, i32::MIN),
+        )
+    }
+
+    pub fn from_points<I>(points: I) -> Cuboid
+    where
+        I: IntoIterator,
+        I::Item: Into<(i32, i32, i32)>,
+    {
+        let mut cuboid = Cuboid::empty();
+        points.into_iter().for_each(|point| cuboid.include(point));
+        cuboid
+    }
+
+    pub fn min(&self) -> (i32, i32, i32) {
+        (self.min_x, self.min_y, self.min_z)
+    }
+
+    pub fn max(&self) -> (i32, i32, i32) {
+        (self.max_x, self.max_y, self.max_z)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_x > self.max_x || self.min_y > self.max_y || self.min_z > self.max_z
+    }
+
+    pub fn width(&self) -> i32 {
+        if self.is_empty() {
+            0
+        } else {
+            self.max_x - self.min_x + 1
+        }
+    }
+
+    pub fn height(&self) -> i32 {
+        if self.is_empty() {
+            0
+        } else {
+            self.max_y - self.min_y + 1
+        }
+    }
+
+    pub fn depth(&self) -> i32 {
+        if self.is_empty() {
+            0
+        } else {
+            self.max_z - self.min_z + 1
+        }
+    }
+
+    pub fn volume(&self) -> i64 {
+        self.width() as i64 * self.height() as i64 * self.depth() as i64
+    }
+
+    pub fn contains(&self, point: impl Into<(i32, i32, i32)>) -> bool {
+        let (x, y, z) = point.into();
+        x >= self.min_x
+            && x <= self.max_x
+            && y >= self.min_y
+            && y <= self.max_y
+            && z >= self.min_z
+            && z <= self.max_z
+    }
+
+    pub fn include(&mut self, point: impl Into<(i32, i32, i32)>) {
+        let (x, y, z) = point.into();
+        self.min_x = self.min_x.min(x);
+        self.min_y = self.min_y.min(y);
+        self.min_z = self.min_z.min(z);
+        self.max_x = self.max_x.max(x);
+        self.max_y = self.max_y.max(y);
+        self.max_z = self.max_z.max(z);
+    }
+
+    pub fn expand(&self, amount: i32) -> Cuboid {
+        if self.is_empty() {
+            return *self;
+        }
+        Cuboid::new(
+            (
+                self.min_x - amount,
+                self.min_y - amount,
+                self.min_z - amount,
+            ),
+            (
+                self.max_x + amount,
+                self.max_y + amount,
+                self.max_z + amount,
+            ),
+        )
+    }
+
+    pub fn intersects(&self, other: &Cuboid) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    pub fn intersection(&self, other: &Cuboid) -> Cuboid {
+        let cuboid = Cuboid::new(
+            (
+                self.min_x.max(other.min_x),
+                self.min_y.max(other.min_y),
+                self.min_z.max(other.min_z),
+            ),
+            (
+                self.max_x.min(other.max_x),
+                self.max_y.min(other.max_y),
+                self.max_z.min(other.max_z),
+            ),
+        );
+        if cuboid.is_empty() {
+            Cuboid::empty()
+        } else {
+            cuboid
+        }
+    }
+
+    pub fn union(&self, other: &Cuboid) -> Cuboid {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        Cuboid::new(
+            (
+                self.min_x.min(other.min_x),
+                self.min_y.min(other.min_y),
+                self.min_z.min(other.min_z),
+            ),
+            (
+                self.max_x.max(other.max_x),
+                self.max_y.max(other.max_y),
+                self.max_z.max(other.max_z),
+            ),
+        )
+    }
+
+    // iterates x fastest, then y, then z - each z slice is a row-major rect
+    pub fn iter(&self) -> impl Iterator<Item = (i32, i32, i32)> {
+        let (min_x, max_x, min_y, max_y) = (self.min_x, self.max_x, self.min_y, self.max_y);
+        (self.min_z..=self.max_z).flat_map(move |z| {
+            (min_y..=max_y).flat_map(move |y| (min_x..=max_x).map(move |x| (x, y, z)))
+        })
+    }
+}
+
+impl Default for Cuboid {
+    fn default() -> Self {
+        Cuboid::empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rect_from_points() {
+        let rect = Rect::from_points([(3, 1), (-1, 4), (2, 2)]);
+        assert_eq!(Rect::new(-1, 1, 3, 4), rect);
+        assert_eq!(5, rect.width());
+        assert_eq!(4, rect.height());
+        assert_eq!(20, rect.area());
+        assert!(Rect::from_points::<[(i32, i32); 0]>([]).is_empty());
+        assert_eq!(0, Rect::empty().area());
+    }
+
+    #[test]
+    fn test_rect_contains() {
+        let rect = Rect::new(0, 0, 2, 2);
+        assert!(rect.contains((0, 0)));
+        assert!(rect.contains((2, 2)));
+        assert!(!rect.contains((3, 2)));
+        assert!(!rect.contains((-1, 0)));
+        assert!(!Rect::empty().contains((0, 0)));
+    }
+
+    #[test]
+    fn test_rect_set_operations() {
+        let left = Rect::new(0, 0, 4, 4);
+        let right = Rect::new(2, 3, 6, 8);
+        assert_eq!(Rect::new(2, 3, 4, 4), left.intersection(&right));
+        assert_eq!(Rect::new(0, 0, 6, 8), left.union(&right));
+        assert!(left.intersects(&right));
+        assert!(!left.intersects(&Rect::new(5, 5, 6, 6)));
+        assert!(left.intersection(&Rect::new(5, 5, 6, 6)).is_empty());
+        assert_eq!(left, left.union(&Rect::empty()));
+    }
+
+    #[test]
+    fn test_rect_expand() {
+        assert_eq!(Rect::new(-1, -1, 3, 3), Rect::new(0, 0, 2, 2).expand(1));
+        assert!(Rect::empty().expand(1).is_empty());
+    }
+
+    #[test]
+    fn test_rect_iter() {
+        let points: Vec<_> = Rect::new(0, 0, 1, 1).iter().collect();
+        assert_eq!(vec![(0, 0), (1, 0), (0, 1), (1, 1)], points);
+        assert_eq!(0, Rect::empty().iter().count());
+    }
+
+    #[test]
+    fn test_cuboid() {
+        let cuboid = Cuboid::from_points([(1, 2, 3), (3, 2, 1)]);
+        assert_eq!(Cuboid::new((1, 2, 1), (3, 2, 3)), cuboid);
+        assert_eq!(9, cuboid.volume());
+        assert_eq!(9, cuboid.iter().count());
+        assert!(cuboid.contains((2, 2, 2)));
+        assert!(!cuboid.contains((2, 3, 2)));
+        assert_eq!(Cuboid::new((0, 1, 0), (4, 3, 4)), cuboid.expand(1));
+        assert_eq!(
+            Cuboid::new((2, 2, 2), (3, 2, 3)),
+            cuboid.intersection(&Cuboid::new((2, 0, 2), (5, 5, 5)))
+        );
+        assert_eq!(
+            Cuboid::new((1, 0, 1), (5, 5, 5)),
+            cuboid.union(&Cuboid::new((2, 0, 2), (5, 5, 5)))
+        );
+        assert_eq!(Some((1, 2, 1)), cuboid.iter().next());
+    }
+}